@@ -1,14 +1,12 @@
-use std::cell::RefCell;
-
 use slotmap::new_key_type;
-use spin_sleep::sleep;
 use ure::{
-	app::{App, AppProxy, WindowSystem, Windows},
-	gpu::GPU,
+	app::{App, AppProxy, FramePacing, Runner, WindowSystem, Windows},
 	two::Visuals2D,
 };
-use ure_data::group::{Data, Group};
-use wgpu::CommandEncoderDescriptor;
+use ure_data::{
+	cell::RefCell,
+	group::{Data, Group},
+};
 use winit::{event_loop::EventLoop, window::WindowAttributes};
 
 #[repr(usize)]
@@ -17,8 +15,6 @@ pub enum Actions {
 	Jump,
 }
 
-const FRAME_TIME: std::time::Duration = std::time::Duration::from_nanos(0_016_666_667);
-
 fn main() {
 	let event_loop = EventLoop::with_user_event().build().unwrap();
 	let mut app: App<Game> = App::new(event_loop.create_proxy());
@@ -38,7 +34,7 @@ new_key_type! {
 
 impl ure::app::Game for Game {
 	fn new(app_proxy: AppProxy) -> Self {
-		let mut data = Data::<GameKey>::with_key();
+		let mut data = Data::<GameKey>::new();
 
 		let mut window_system = WindowSystem::new(app_proxy.clone());
 
@@ -50,7 +46,8 @@ impl ure::app::Game for Game {
 			.borrow_mut()
 			.new(1)
 			.with::<Windows>(vec![WindowAttributes::default().with_title("URE")])
-			.done();
+			.done()
+			.unwrap();
 		window_system.inspect_capabilities(&data);
 
 		let format = window_system.surface_format().unwrap();
@@ -68,35 +65,19 @@ impl ure::app::Game for Game {
 	}
 
 	fn run(self) {
-		let mut frame_start;
-		let mut delta = std::time::Duration::ZERO;
-		'game: loop {
-			frame_start = std::time::Instant::now();
-
-			// ================================ INPUT ================================
-			if self.window_system.close(&self.data) {
-				break 'game;
-			}
-
-			// ============================== RENDERING ==============================
-			self.window_system.reconfigure(&self.data);
-			let mut encoder = GPU
-				.device
-				.create_command_encoder(&CommandEncoderDescriptor::default());
-
-			// RENDER PASSES
-			{
-				let pass = self.visuals_2d.begin_pass(&mut encoder, view);
-				self.visuals_2d.render(&self.data, &mut pass);
-			}
-
-			GPU.queue.submit([encoder.finish()]);
-			self.window_system.present(&self.data);
-
-			// =============================== TIMING ================================
-			sleep(FRAME_TIME.saturating_sub(frame_start.elapsed()));
-			delta = frame_start.elapsed();
-		}
-		self.app_proxy.exit();
+		let Game {
+			app_proxy,
+			data,
+			window_system,
+			visuals_2d,
+		} = self;
+		Runner::new(app_proxy, window_system)
+			.pacing(FramePacing::Fps(60.0))
+			.on_render(move |data, encoder, view| {
+				let mut pass = visuals_2d.begin_pass(encoder, view);
+				visuals_2d.render(data, &mut pass);
+			})
+			.run(data)
+			.unwrap();
 	}
 }
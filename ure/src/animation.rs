@@ -0,0 +1,636 @@
+//! Keyframe animation of component values.
+//!
+//! A [Player] keeps the time of its row. [Clip]s of [Track]s sample that time
+//! and set fields of the row's values, such as [Transforms2D] and [Colors].
+
+use std::{error::Error, f32::consts::PI, time::Duration};
+
+use glam::{Affine2, Vec2};
+use ure_data::{
+	cell::MaybeSync,
+	component,
+	components::{Component, MissingDependency},
+	containers::{NewDefault, SparseSet, new_with_or_default},
+	glob::{CompMut, CompRef},
+	group::{Data, Group},
+	signal,
+};
+
+use crate::{
+	gpu::Srgba,
+	two::{Colors, Transforms2D},
+};
+
+/// Curve from one keyframe to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Ease {
+	#[default]
+	Linear,
+	/// Holds the value until the next keyframe.
+	Step,
+	QuadIn,
+	QuadOut,
+	QuadInOut,
+	CubicIn,
+	CubicOut,
+	CubicInOut,
+	SineIn,
+	SineOut,
+	SineInOut,
+	/// CSS style curve through `(0, 0)`, both control points and `(1, 1)`.
+	/// Control points must have `x` within `0..=1`.
+	CubicBezier(Vec2, Vec2),
+}
+impl Ease {
+	/// Maps progress `t` within `0..=1` between two keyframes.
+	pub fn apply(self, t: f32) -> f32 {
+		match self {
+			Self::Linear => t,
+			Self::Step => 0.0,
+			Self::QuadIn => t * t,
+			Self::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+			Self::QuadInOut => {
+				if t < 0.5 {
+					2.0 * t * t
+				} else {
+					1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+				}
+			}
+			Self::CubicIn => t * t * t,
+			Self::CubicOut => 1.0 - (1.0 - t).powi(3),
+			Self::CubicInOut => {
+				if t < 0.5 {
+					4.0 * t * t * t
+				} else {
+					1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+				}
+			}
+			Self::SineIn => 1.0 - (t * PI / 2.0).cos(),
+			Self::SineOut => (t * PI / 2.0).sin(),
+			Self::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+			Self::CubicBezier(p1, p2) => {
+				let bezier = |s: f32, a: f32, b: f32| {
+					let u = 1.0 - s;
+					3.0 * u * u * s * a + 3.0 * u * s * s * b + s * s * s
+				};
+				// `x` grows with `s`, so bisect for the `s` giving `t`.
+				let (mut low, mut high) = (0.0, 1.0);
+				for _ in 0..24 {
+					let mid = (low + high) / 2.0;
+					if bezier(mid, p1.x, p2.x) < t {
+						low = mid;
+					} else {
+						high = mid;
+					}
+				}
+				bezier((low + high) / 2.0, p1.y, p2.y)
+			}
+		}
+	}
+}
+
+/// Values that can be blended between keyframes.
+pub trait Lerp: Clone + MaybeSync + 'static {
+	fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+impl Lerp for f32 {
+	fn lerp(&self, other: &Self, t: f32) -> Self {
+		self + (other - self) * t
+	}
+}
+impl Lerp for Vec2 {
+	fn lerp(&self, other: &Self, t: f32) -> Self {
+		Vec2::lerp(*self, *other, t)
+	}
+}
+impl Lerp for Affine2 {
+	/// Blends each column, animate translation, angle and scale separately
+	/// for rotations.
+	fn lerp(&self, other: &Self, t: f32) -> Self {
+		Affine2::from_cols(
+			self.matrix2.x_axis.lerp(other.matrix2.x_axis, t),
+			self.matrix2.y_axis.lerp(other.matrix2.y_axis, t),
+			self.translation.lerp(other.translation, t),
+		)
+	}
+}
+impl Lerp for Srgba {
+	fn lerp(&self, other: &Self, t: f32) -> Self {
+		self.lerp_rect(*other, t)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframe<T> {
+	pub time: f32,
+	pub value: T,
+	/// Curve towards the next keyframe.
+	pub ease: Ease,
+}
+
+/// Keyframes setting one field of a row's value `E`.
+pub struct Track<E, T> {
+	keyframes: Vec<Keyframe<T>>,
+	field: fn(&mut E, T),
+}
+impl<E, T: Lerp> Track<E, T> {
+	pub fn new(field: fn(&mut E, T)) -> Self {
+		Self {
+			keyframes: Vec::new(),
+			field,
+		}
+	}
+	/// Adds a keyframe, keeping keyframes ordered by time.
+	pub fn key(mut self, time: f32, value: T, ease: Ease) -> Self {
+		let i = self.keyframes.partition_point(|k| k.time <= time);
+		self.keyframes.insert(i, Keyframe { time, value, ease });
+		self
+	}
+	pub fn keyframes(&self) -> &[Keyframe<T>] {
+		&self.keyframes
+	}
+	/// Time of the last keyframe.
+	pub fn duration(&self) -> f32 {
+		self.keyframes.last().map_or(0.0, |k| k.time)
+	}
+	/// Value at `time`, held before the first and after the last keyframe.
+	pub fn sample(&self, time: f32) -> Option<T> {
+		let next = self.keyframes.partition_point(|k| k.time <= time);
+		if next == 0 {
+			return Some(self.keyframes.first()?.value.clone());
+		}
+		let from = &self.keyframes[next - 1];
+		let Some(to) = self.keyframes.get(next) else {
+			return Some(from.value.clone());
+		};
+		let t = (time - from.time) / (to.time - from.time);
+		Some(from.value.lerp(&to.value, from.ease.apply(t)))
+	}
+}
+
+/// A [Track] with its value type erased.
+pub trait Channel<E>: MaybeSync {
+	fn apply(&self, time: f32, target: &mut E);
+	fn duration(&self) -> f32;
+}
+impl<E: 'static, T: Lerp> Channel<E> for Track<E, T> {
+	fn apply(&self, time: f32, target: &mut E) {
+		if let Some(value) = self.sample(time) {
+			(self.field)(target, value);
+		}
+	}
+	fn duration(&self) -> f32 {
+		Track::duration(self)
+	}
+}
+
+/// Tracks played together on one row.
+pub struct Clip<E> {
+	channels: Vec<Box<dyn Channel<E>>>,
+}
+impl<E> Default for Clip<E> {
+	fn default() -> Self {
+		Self {
+			channels: Vec::new(),
+		}
+	}
+}
+impl<E: 'static> Clip<E> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	pub fn with<T: Lerp>(mut self, track: Track<E, T>) -> Self {
+		self.channels.push(Box::new(track));
+		self
+	}
+	/// Time of the last keyframe of any track.
+	pub fn duration(&self) -> f32 {
+		self.channels
+			.iter()
+			.map(|c| c.duration())
+			.fold(0.0, f32::max)
+	}
+	pub fn apply(&self, time: f32, target: &mut E) {
+		for channel in self.channels.iter() {
+			channel.apply(time, target);
+		}
+	}
+}
+
+pub fn set_translation(transform: &mut Affine2, translation: Vec2) {
+	transform.translation = translation;
+}
+/// Keeps the scale and translation.
+pub fn set_angle(transform: &mut Affine2, angle: f32) {
+	let (scale, _, translation) = transform.to_scale_angle_translation();
+	*transform = Affine2::from_scale_angle_translation(scale, angle, translation);
+}
+/// Keeps the angle and translation.
+pub fn set_scale(transform: &mut Affine2, scale: Vec2) {
+	let (_, angle, translation) = transform.to_scale_angle_translation();
+	*transform = Affine2::from_scale_angle_translation(scale, angle, translation);
+}
+pub fn set_color(color: &mut Srgba, value: Srgba) {
+	*color = value;
+}
+pub fn set_alpha(color: &mut Srgba, alpha: f32) {
+	*color = color.with_alpha(alpha);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Playback {
+	/// Stops at the end, running [ANIMATION_FINISHED].
+	#[default]
+	Once,
+	/// Starts over at the end, running [ANIMATION_LOOPED].
+	Loop,
+	/// Plays backwards at the end, running [ANIMATION_LOOPED] once back at
+	/// the start.
+	PingPong,
+}
+
+/// Playback state of a row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+	pub duration: f32,
+	pub speed: f32,
+	pub mode: Playback,
+	pub playing: bool,
+	/// Time played within one cycle, twice the duration for ping pong.
+	phase: f32,
+}
+impl Player {
+	pub fn new(duration: f32, mode: Playback) -> Self {
+		Self {
+			duration,
+			speed: 1.0,
+			mode,
+			playing: true,
+			phase: 0.0,
+		}
+	}
+	/// Plays as long as `clip`.
+	pub fn of<E: 'static>(clip: &Clip<E>, mode: Playback) -> Self {
+		Self::new(clip.duration(), mode)
+	}
+	/// Time within the clips.
+	pub fn time(&self) -> f32 {
+		if self.phase > self.duration {
+			2.0 * self.duration - self.phase
+		} else {
+			self.phase
+		}
+	}
+	pub fn seek(&mut self, time: f32) {
+		self.phase = time.clamp(0.0, self.duration);
+	}
+	/// Restarts from the beginning.
+	pub fn play(&mut self) {
+		self.phase = 0.0;
+		self.playing = true;
+	}
+	/// Advances by `delta` seconds, returning whether the end, or for loops
+	/// the end of a cycle, was reached.
+	pub fn advance(&mut self, delta: f32) -> bool {
+		if !self.playing || self.duration <= 0.0 {
+			return false;
+		}
+		self.phase += delta * self.speed;
+		match self.mode {
+			Playback::Once => {
+				if (0.0..self.duration).contains(&self.phase) {
+					return false;
+				}
+				self.phase = self.phase.clamp(0.0, self.duration);
+				self.playing = false;
+				true
+			}
+			Playback::Loop | Playback::PingPong => {
+				let cycle = match self.mode {
+					Playback::PingPong => 2.0 * self.duration,
+					_ => self.duration,
+				};
+				if (0.0..cycle).contains(&self.phase) {
+					return false;
+				}
+				self.phase = self.phase.rem_euclid(cycle);
+				true
+			}
+		}
+	}
+}
+
+/// Adds `C` unless the group has it, with an empty row for each it has.
+fn add_sparse<C: Component>(group: &mut Group) -> Result<(), MissingDependency>
+where
+	C::Container: NewDefault + Default,
+{
+	if group.contains_component::<C>() {
+		return Ok(());
+	}
+	let mut container = C::Container::default();
+	container.new_default(group.len());
+	group.add_container::<C>(container)
+}
+component!(pub Players: SparseSet<Player>, new_with_or_default::<Players>, Vec<Option<Player>>);
+component!(pub TransformClips2D: SparseSet<Clip<Affine2>>, new_with_or_default::<TransformClips2D>, Vec<Option<Clip<Affine2>>>);
+component!(pub ColorClips: SparseSet<Clip<Srgba>>, new_with_or_default::<ColorClips>, Vec<Option<Clip<Srgba>>>);
+
+signal!(pub ANIMATION_FINISHED: &[usize]);
+signal!(pub ANIMATION_LOOPED: &[usize]);
+
+/// Advances every [Player] by `delta` seconds. Rows that finished are left in
+/// `finished`, rows that looped in `looped`.
+pub fn advance_players(
+	CompMut(mut players): CompMut<Players>,
+	(delta, finished, looped): &mut (f32, Vec<usize>, Vec<usize>),
+) {
+	for (row, player) in players.iter_mut() {
+		if player.advance(*delta) {
+			match player.mode {
+				Playback::Once => finished.push(row),
+				Playback::Loop | Playback::PingPong => looped.push(row),
+			}
+		}
+	}
+}
+pub fn animate_transforms_2d(
+	CompRef((players, clips)): CompRef<(Players, TransformClips2D)>,
+	CompMut(mut transforms): CompMut<Transforms2D>,
+	_: &mut (),
+) {
+	for (row, clip) in clips.iter() {
+		if let Some(player) = players.get(row) {
+			clip.apply(player.time(), &mut transforms[row]);
+		}
+	}
+}
+pub fn animate_colors(
+	CompRef((players, clips)): CompRef<(Players, ColorClips)>,
+	CompMut(mut colors): CompMut<Colors>,
+	_: &mut (),
+) {
+	for (row, clip) in clips.iter() {
+		if let Some(player) = players.get(row) {
+			clip.apply(player.time(), &mut colors[row]);
+		}
+	}
+}
+
+/// Plays the animations of groups.
+pub struct Animator<Key: slotmap::Key> {
+	keys: Vec<Key>,
+}
+impl<Key: slotmap::Key> Default for Animator<Key> {
+	fn default() -> Self {
+		Self { keys: Vec::new() }
+	}
+}
+impl<Key: slotmap::Key> Animator<Key> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	/// Adds [Players], and clips for the [Transforms2D] and [Colors] the group
+	/// has. Components the group has already are kept, rows it has get no
+	/// player or clip.
+	pub fn add(&mut self, data: &Data<Key>, key: Key) -> Result<(), MissingDependency> {
+		let Some(group) = data.get(key) else {
+			return Ok(());
+		};
+		let mut group = group.borrow_mut();
+		add_sparse::<Players>(&mut group)?;
+		if group.contains_component::<Transforms2D>() {
+			add_sparse::<TransformClips2D>(&mut group)?;
+		}
+		if group.contains_component::<Colors>() {
+			add_sparse::<ColorClips>(&mut group)?;
+		}
+		if !self.keys.contains(&key) {
+			self.keys.push(key);
+		}
+		Ok(())
+	}
+	/// Advances players and writes every clip, then runs
+	/// [ANIMATION_FINISHED] and [ANIMATION_LOOPED], whose methods see the
+	/// [Data]'s resources.
+	pub fn update(&mut self, data: &Data<Key>, delta: Duration) -> Result<(), Box<dyn Error>> {
+		for key in self.keys.iter() {
+			let Some(mut globule) = data.globule_mut(*key) else {
+				continue;
+			};
+			let mut args = (delta.as_secs_f32(), Vec::new(), Vec::new());
+			globule.as_ref().call_method(advance_players, &mut args)?;
+			if globule.group().contains_component::<TransformClips2D>() {
+				globule
+					.as_ref()
+					.call_method(animate_transforms_2d, &mut ())?;
+			}
+			if globule.group().contains_component::<ColorClips>() {
+				globule.as_ref().call_method(animate_colors, &mut ())?;
+			}
+			let (_, finished, looped) = args;
+			if !finished.is_empty() {
+				globule.call_signal(&ANIMATION_FINISHED, finished.as_slice())?;
+			}
+			if !looped.is_empty() {
+				globule.call_signal(&ANIMATION_LOOPED, looped.as_slice())?;
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use slotmap::DefaultKey;
+
+	use super::*;
+
+	#[test]
+	fn add_keeps_the_components_a_group_has() {
+		let mut data = Data::<DefaultKey>::new();
+		let mut group = Group::default();
+		group.add_component::<Transforms2D>().unwrap();
+		group.add_component::<Players>().unwrap();
+		group
+			.new(2)
+			.with::<Players>(vec![None, Some(Player::new(1.0, Playback::Once))])
+			.done()
+			.unwrap();
+		let key = data.insert(group.into());
+		let mut animator = Animator::new();
+		animator.add(&data, key).unwrap();
+		animator.add(&data, key).unwrap();
+		assert_eq!(animator.keys, [key]);
+		let mut group = data[key].borrow_mut();
+		assert!(group.contains_component::<TransformClips2D>());
+		assert!(!group.contains_component::<ColorClips>());
+		assert_eq!(group.summary().diverging().count(), 0);
+		assert!(
+			group
+				.borrow_container::<Players>()
+				.unwrap()
+				.get(1)
+				.is_some()
+		);
+		group.new(1).done().unwrap();
+		assert_eq!(group.summary().diverging().count(), 0);
+	}
+
+	fn close(a: f32, b: f32) -> bool {
+		(a - b).abs() < 1e-4
+	}
+
+	#[test]
+	fn sample_holds_outside_the_keyframes() {
+		let track = Track::new(set_angle)
+			.key(2.0, 20.0, Ease::Linear)
+			.key(0.0, 0.0, Ease::Linear)
+			.key(1.0, 10.0, Ease::Step);
+		let times: Vec<f32> = track.keyframes().iter().map(|k| k.time).collect();
+		assert_eq!(times, [0.0, 1.0, 2.0]);
+		assert_eq!(track.duration(), 2.0);
+		assert_eq!(track.sample(-1.0), Some(0.0));
+		assert_eq!(track.sample(0.25), Some(2.5));
+		assert_eq!(track.sample(1.0), Some(10.0));
+		// Step holds until the next keyframe.
+		assert_eq!(track.sample(1.9), Some(10.0));
+		assert_eq!(track.sample(2.0), Some(20.0));
+		assert_eq!(track.sample(3.0), Some(20.0));
+		assert_eq!(Track::new(set_angle).sample(0.0), None);
+	}
+
+	#[test]
+	fn eases_start_and_end_on_the_keyframes() {
+		let bezier = Ease::CubicBezier(Vec2::new(0.25, 0.1), Vec2::new(0.25, 1.0));
+		for (ease, half) in [
+			(Ease::Linear, 0.5),
+			(Ease::QuadIn, 0.25),
+			(Ease::QuadOut, 0.75),
+			(Ease::QuadInOut, 0.5),
+			(Ease::CubicIn, 0.125),
+			(Ease::CubicOut, 0.875),
+			(Ease::CubicInOut, 0.5),
+			(Ease::SineIn, 1.0 - (PI / 4.0).cos()),
+			(Ease::SineOut, (PI / 4.0).sin()),
+			(Ease::SineInOut, 0.5),
+			// CSS `ease`.
+			(bezier, 0.8024),
+		] {
+			assert!(close(ease.apply(0.0), 0.0), "{ease:?} at 0");
+			assert!(close(ease.apply(0.5), half), "{ease:?} at 0.5");
+			assert!(close(ease.apply(1.0), 1.0), "{ease:?} at 1");
+		}
+		assert_eq!(Ease::Step.apply(0.99), 0.0);
+		let linear = Ease::CubicBezier(Vec2::splat(1.0 / 3.0), Vec2::splat(2.0 / 3.0));
+		assert!(close(linear.apply(0.3), 0.3));
+	}
+
+	#[test]
+	fn once_stops_at_either_end() {
+		let mut player = Player::new(1.0, Playback::Once);
+		assert!(!player.advance(0.5));
+		assert!(player.advance(0.75));
+		assert_eq!(player.time(), 1.0);
+		assert!(!player.playing);
+		assert!(!player.advance(0.5));
+
+		player.play();
+		player.seek(0.5);
+		player.speed = -1.0;
+		assert!(!player.advance(0.25));
+		assert_eq!(player.time(), 0.25);
+		assert!(player.advance(0.5));
+		assert_eq!(player.time(), 0.0);
+		assert!(!player.playing);
+	}
+
+	#[test]
+	fn loop_wraps_both_ways() {
+		let mut player = Player::new(1.0, Playback::Loop);
+		assert!(!player.advance(0.75));
+		assert!(player.advance(0.5));
+		assert_eq!(player.time(), 0.25);
+		player.speed = -1.0;
+		assert!(player.advance(0.5));
+		assert_eq!(player.time(), 0.75);
+		assert!(player.playing);
+	}
+
+	#[test]
+	fn ping_pong_plays_back() {
+		let mut player = Player::new(1.0, Playback::PingPong);
+		assert!(!player.advance(1.5));
+		assert_eq!(player.time(), 0.5);
+		assert!(player.advance(0.75));
+		assert_eq!(player.time(), 0.25);
+		player.speed = -1.0;
+		assert!(player.advance(0.5));
+		assert_eq!(player.time(), 0.25);
+	}
+
+	#[test]
+	fn update_animates_and_reports_rows() {
+		let mut data = Data::<DefaultKey>::new();
+		data.insert_resource(Vec::<(&str, Vec<usize>)>::new());
+		let mut group = Group::default();
+		group.add_component::<Transforms2D>().unwrap();
+		group.add_component::<Players>().unwrap();
+		group.add_component::<TransformClips2D>().unwrap();
+		let clip = Clip::new().with(
+			Track::new(set_translation)
+				.key(0.0, Vec2::ZERO, Ease::Linear)
+				.key(1.0, Vec2::new(10.0, 0.0), Ease::Linear),
+		);
+		group
+			.new(4)
+			.with::<Players>(vec![
+				Some(Player::of(&clip, Playback::Once)),
+				None,
+				Some(Player::new(0.5, Playback::Loop)),
+				Some(Player::new(1.0, Playback::PingPong)),
+			])
+			.with::<TransformClips2D>(vec![Some(clip), None, None, None])
+			.done()
+			.unwrap();
+		for (signal, name) in [
+			(&ANIMATION_FINISHED, "finished"),
+			(&ANIMATION_LOOPED, "looped"),
+		] {
+			group.connect_signal(signal, move |glob, rows| {
+				glob.resource_mut::<Vec<(&str, Vec<usize>)>>()
+					.ok_or("Missing log.")?
+					.push((name, rows.to_vec()));
+				Ok(())
+			});
+		}
+		let key = data.insert(group.into());
+		let mut animator = Animator::new();
+		animator.add(&data, key).unwrap();
+
+		animator.update(&data, Duration::from_millis(600)).unwrap();
+		let x = data[key]
+			.borrow()
+			.borrow_container::<Transforms2D>()
+			.unwrap()[0]
+			.translation
+			.x;
+		assert!(close(x, 6.0));
+		animator.update(&data, Duration::from_millis(600)).unwrap();
+		let x = data[key]
+			.borrow()
+			.borrow_container::<Transforms2D>()
+			.unwrap()[0]
+			.translation
+			.x;
+		assert!(close(x, 10.0));
+		assert_eq!(
+			*data.resource::<Vec<(&str, Vec<usize>)>>().unwrap(),
+			[
+				("looped", vec![2]),
+				("finished", vec![0]),
+				("looped", vec![2])
+			]
+		);
+	}
+}
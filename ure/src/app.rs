@@ -4,15 +4,17 @@ use std::{
 	sync::{Arc, atomic::AtomicBool},
 };
 
+#[cfg(feature = "reflect")]
+use ure_data::reflect::{FieldInfo, Reflect, ReflectError, ReflectVTable, Value};
 use ure_data::{
 	component,
 	components::NewArgs,
-	containers::{IndexSet, One},
-	glob::{CompMut, CompRef, ContMut, Glob, Len},
+	containers::IndexSet,
+	glob::{CompMut, CompRef, ContMut, Glob, Len, Res},
 	group::Data,
-	method::MethodTrait,
+	signals::SignalError,
 };
-use wgpu::{Surface, SurfaceCapabilities, SurfaceTexture, TextureFormat};
+use wgpu::{PresentMode, Surface, SurfaceCapabilities, SurfaceTexture, TextureFormat, TextureView};
 use winit::{
 	application::ApplicationHandler,
 	dpi::PhysicalSize,
@@ -22,32 +24,78 @@ use winit::{
 
 use crate::gpu::GPU;
 
+pub mod headless;
 pub mod input;
+pub mod runner;
+pub mod time;
+
+pub use headless::HeadlessApp;
+pub use runner::{FramePacing, Runner};
+pub use time::Time;
 
 pub type Input = Arc<input::Input>;
+/// Handle from the game thread to the [App], or to a [HeadlessApp].
+#[derive(Debug, Clone)]
+pub struct AppProxy {
+	inner: ProxyInner,
+	input: Input,
+}
 #[derive(Debug, Clone)]
-pub struct AppProxy(EventLoopProxy<Event>);
+enum ProxyInner {
+	EventLoop(EventLoopProxy<Event>),
+	Headless(Arc<headless::HeadlessState>),
+}
 impl AppProxy {
+	pub fn input(&self) -> &Input {
+		&self.input
+	}
+	pub fn is_headless(&self) -> bool {
+		matches!(self.inner, ProxyInner::Headless(_))
+	}
+	/// Headless proxies create no windows.
 	pub fn new_windows(&self, attrs: Vec<WindowAttributes>) -> Vec<Window> {
+		let ProxyInner::EventLoop(proxy) = &self.inner else {
+			return Vec::new();
+		};
 		let (send, recv) = oneshot::channel();
-		self.0.send_event(Event::NewWindow(attrs, send)).unwrap();
+		proxy.send_event(Event::NewWindow(attrs, send)).unwrap();
 		recv.recv().unwrap()
 	}
 	pub fn recv_exits(&self, ids: Vec<WindowId>) -> Vec<Arc<AtomicBool>> {
+		let proxy = match &self.inner {
+			ProxyInner::EventLoop(proxy) => proxy,
+			ProxyInner::Headless(state) => {
+				return ids.iter().map(|_| state.closed.clone()).collect();
+			}
+		};
 		let (send, recv) = oneshot::channel();
-		self.0.send_event(Event::RecvExits(ids, send)).unwrap();
+		proxy.send_event(Event::RecvExits(ids, send)).unwrap();
 		recv.recv().unwrap()
 	}
+	pub fn close_windows(&self, ids: Vec<WindowId>) {
+		if let ProxyInner::EventLoop(proxy) = &self.inner {
+			proxy.send_event(Event::CloseWindows(ids)).unwrap();
+		}
+	}
 	pub fn exit(&self) {
-		self.0.send_event(Event::Exit).unwrap();
+		match &self.inner {
+			ProxyInner::EventLoop(proxy) => proxy.send_event(Event::Exit).unwrap(),
+			ProxyInner::Headless(state) => state.exit(),
+		}
+	}
+	/// Whether a headless app was closed or exited, always false otherwise.
+	fn headless_closed(&self) -> bool {
+		match &self.inner {
+			ProxyInner::EventLoop(_) => false,
+			ProxyInner::Headless(state) => state.is_closed(),
+		}
 	}
 }
 
-component!(pub Proxy: One<AppProxy>);
-component!(pub Windows: Vec<Arc<Window>>, new_windows, Vec<WindowAttributes>);
+component!(pub Windows: Vec<Arc<Window>>, new_windows, Vec<WindowAttributes>; delete_windows);
 pub fn new_windows(
 	ContMut(mut windows): ContMut<Windows>,
-	CompRef(app_proxy): CompRef<Proxy>,
+	Res(app_proxy): Res<AppProxy>,
 	args: &mut NewArgs,
 ) {
 	let attrs = args
@@ -61,11 +109,20 @@ pub fn new_windows(
 	);
 }
 
+pub fn delete_windows(
+	CompRef(windows): CompRef<Windows>,
+	Res(app_proxy): Res<AppProxy>,
+	indices: &mut &[usize],
+) {
+	app_proxy.close_windows(indices.iter().map(|&i| windows[i].id()).collect());
+}
+
 component!(pub WindowExits: Vec<Arc<AtomicBool>>, new_window_exits);
 pub fn new_window_exits(
 	Len(len): Len,
 	ContMut(mut window_exits): ContMut<WindowExits>,
-	CompRef((windows, app_proxy)): CompRef<(Windows, Proxy)>,
+	CompRef(windows): CompRef<Windows>,
+	Res(app_proxy): Res<AppProxy>,
 	args: &mut NewArgs,
 ) {
 	window_exits.extend(
@@ -89,6 +146,43 @@ pub fn new_window_ids(
 	}
 }
 component!(pub WindowSizes: Vec<PhysicalSize<u32>>);
+/// [PhysicalSize] is foreign, so its reflection is written by hand.
+#[cfg(feature = "reflect")]
+const WINDOW_SIZES_REFLECT: ReflectVTable = ReflectVTable {
+	type_name: "PhysicalSize<u32>",
+	fields: || {
+		vec![
+			FieldInfo {
+				name: "width",
+				type_name: "u32",
+			},
+			FieldInfo {
+				name: "height",
+				type_name: "u32",
+			},
+		]
+	},
+	get: |sizes, row| {
+		let size = sizes.downcast_ref::<Vec<PhysicalSize<u32>>>()?.get(row)?;
+		Some(Value::Struct(vec![
+			("width".into(), size.width.to_value()),
+			("height".into(), size.height.to_value()),
+		]))
+	},
+	set: |sizes, row, value| {
+		let size = sizes
+			.downcast_mut::<Vec<PhysicalSize<u32>>>()
+			.and_then(|sizes| sizes.get_mut(row))
+			.ok_or(ReflectError::MissingRow(row))?;
+		if let Some(width) = value.field("width") {
+			size.width.set_value(width)?;
+		}
+		if let Some(height) = value.field("height") {
+			size.height.set_value(height)?;
+		}
+		Ok(())
+	},
+};
 component!(pub Surfaces: Vec<Surface<'static>>, new_surfaces);
 pub fn new_surfaces(
 	Len(len): Len,
@@ -106,6 +200,7 @@ pub struct WindowSystem<Key: slotmap::Key> {
 	glob: Glob<Key, WindowId, WindowIds>,
 	proxy: AppProxy,
 	capabilities: Option<SurfaceCapabilities>,
+	present_mode: Option<PresentMode>,
 }
 impl<Key: slotmap::Key> WindowSystem<Key> {
 	pub fn new(proxy: AppProxy) -> Self {
@@ -113,66 +208,94 @@ impl<Key: slotmap::Key> WindowSystem<Key> {
 			glob: Glob::new(),
 			proxy,
 			capabilities: None,
+			present_mode: None,
 		}
 	}
+	/// Headless, only the [AppProxy] resource is inserted, the group gets no
+	/// window or surface components.
 	pub fn add(&mut self, data: &Data<Key>, key: Key) {
 		let Some(group) = data.get(key) else {
 			return;
 		};
 		let mut group = group.borrow_mut();
-		group
-			.add_container::<Proxy>(One(self.proxy.clone()))
-			.unwrap();
+		group.insert_resource(self.proxy.clone());
+		if self.proxy.is_headless() {
+			return;
+		}
 		group.add_component::<Windows>().unwrap();
 		group.add_component::<WindowExits>().unwrap();
 		group.add_component::<WindowSizes>().unwrap();
 		group.add_component::<Surfaces>().unwrap();
 		group.add_component::<SurfaceTextures>().unwrap();
+		#[cfg(feature = "reflect")]
+		group
+			.set_reflect::<WindowSizes>(WINDOW_SIZES_REFLECT)
+			.unwrap();
 		self.glob.add_group(key)
 	}
 	/// Call this function AFTER creating windows.
 	pub fn inspect_capabilities(&mut self, data: &Data<Key>) {
-		self.capabilities = Some(
-			data.get(self.glob[0])
-				.unwrap()
-				.borrow()
-				.borrow_component::<Surfaces>()
-				.unwrap()[0]
-				.get_capabilities(&GPU.adapter),
-		)
+		if self.proxy.is_headless() {
+			return;
+		}
+		let globule = self.glob.iter(data).next().unwrap();
+		let group = globule.group();
+		self.capabilities =
+			Some(group.borrow_component::<Surfaces>().unwrap()[0].get_capabilities(&GPU.adapter))
 	}
 	/// [Self::inspect_capabilities]
 	pub fn surface_format(&self) -> Option<TextureFormat> {
 		self.capabilities.as_ref().map(|c| c.formats[0])
 	}
-	pub fn close(&self, data: &Data<Key>) -> bool {
+	/// Applies to surfaces configured afterwards, as windows are created or
+	/// resized. [None] uses the first mode the surface supports.
+	pub fn set_present_mode(&mut self, present_mode: Option<PresentMode>) {
+		self.present_mode = present_mode;
+	}
+	/// Headless, returns whether the [HeadlessApp] was closed or exited.
+	pub fn close(&self, data: &Data<Key>) -> Result<bool, SignalError> {
+		if self.proxy.is_headless() {
+			return Ok(self.proxy.headless_closed());
+		}
 		let mut all_closed = true;
 		for mut globule in self.glob.iter_mut(data) {
-			let mut globule_mut = globule.as_mut();
-			let Ok(delete) = globule_mut.call_method(close_windows, &mut ()) else {
+			let Ok(delete) = globule.as_ref().call_method(close_windows, &mut ()) else {
 				continue;
 			};
-			let group = globule_mut.group();
-			group.delete(&delete);
-			all_closed &= group.is_empty();
+			globule.delete(&delete)?;
+			all_closed &= globule.group().is_empty();
 		}
-		all_closed
+		Ok(all_closed)
 	}
 	pub fn reconfigure(&self, data: &Data<Key>) {
 		for globule in self.glob.iter(data) {
-			globule.as_ref().call_method(reconfigure_surfaces, &mut ());
+			// Fails only if the group's window components are borrowed
+			// elsewhere, then its surfaces are left for the next frame.
+			_ = globule
+				.as_ref()
+				.call_method(reconfigure_surfaces, &mut self.present_mode.clone());
 		}
 	}
+	/// Views of the surface textures acquired by [Self::reconfigure].
+	pub fn views(&self, data: &Data<Key>) -> Vec<TextureView> {
+		let mut views = Vec::new();
+		for globule in self.glob.iter(data) {
+			_ = globule.as_ref().call_method(surface_views, &mut views);
+		}
+		views
+	}
 	pub fn present(&self, data: &Data<Key>) {
 		for globule in self.glob.iter(data) {
-			globule.as_ref().call_method(present_surfaces, &mut ());
+			// As in reconfigure, a borrowed group is skipped for this frame.
+			_ = globule.as_ref().call_method(present_surfaces, &mut ());
 		}
 	}
 }
 
 pub fn close_windows(CompRef(window_exits): CompRef<WindowExits>, _: &mut ()) -> Vec<usize> {
 	let mut delete = Vec::new();
-	for (i, c) in window_exits.iter().enumerate() {
+	// Descending, so swap removal never moves a row that is yet to be deleted.
+	for (i, c) in window_exits.iter().enumerate().rev() {
 		if c.load(std::sync::atomic::Ordering::Relaxed) {
 			delete.push(i);
 		}
@@ -183,22 +306,28 @@ pub fn reconfigure_surfaces(
 	Len(len): Len,
 	CompRef((windows, surfaces)): CompRef<(Windows, Surfaces)>,
 	CompMut((mut sizes, mut textures)): CompMut<(WindowSizes, SurfaceTextures)>,
-	_: &mut (),
+	present_mode: &mut Option<PresentMode>,
 ) {
 	for i in 0..len {
 		let window_size = windows[i].inner_size();
 		if sizes[i] != window_size {
 			sizes[i] = window_size;
-			surfaces[i].configure(
-				&GPU.device,
-				&surfaces[i]
-					.get_default_config(&GPU.adapter, window_size.width, window_size.height)
-					.unwrap(),
-			);
+			let mut config = surfaces[i]
+				.get_default_config(&GPU.adapter, window_size.width, window_size.height)
+				.unwrap();
+			if let Some(present_mode) = *present_mode {
+				config.present_mode = present_mode;
+			}
+			surfaces[i].configure(&GPU.device, &config);
 		}
 		textures[i] = surfaces[i].get_current_texture().ok();
 	}
 }
+pub fn surface_views(CompRef(textures): CompRef<SurfaceTextures>, views: &mut Vec<TextureView>) {
+	for texture in textures.iter().flatten() {
+		views.push(texture.texture.create_view(&Default::default()));
+	}
+}
 pub fn present_surfaces(CompMut(mut textures): CompMut<SurfaceTextures>, _: &mut ()) {
 	for texture in textures.iter_mut() {
 		if let Some(texture) = texture.take() {
@@ -211,6 +340,7 @@ pub fn present_surfaces(CompMut(mut textures): CompMut<SurfaceTextures>, _: &mut
 pub enum Event {
 	NewWindow(Vec<WindowAttributes>, oneshot::Sender<Vec<Window>>),
 	RecvExits(Vec<WindowId>, oneshot::Sender<Vec<Arc<AtomicBool>>>),
+	CloseWindows(Vec<WindowId>),
 	Exit,
 }
 
@@ -236,7 +366,10 @@ pub struct App<G: Game> {
 }
 impl<G: Game> ApplicationHandler<Event> for App<G> {
 	fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
-		let proxy = AppProxy(self.proxy.clone());
+		let proxy = AppProxy {
+			inner: ProxyInner::EventLoop(self.proxy.clone()),
+			input: self.input.clone(),
+		};
 
 		self.game = Some(std::thread::spawn(move || {
 			let game = G::new(proxy);
@@ -253,12 +386,12 @@ impl<G: Game> ApplicationHandler<Event> for App<G> {
 		_ = (event_loop, window_id);
 		match event {
 			// winit::event::WindowEvent::Resized(_) => todo!(),
-			winit::event::WindowEvent::CloseRequested => self
-				.windows
-				.get(&window_id)
-				.unwrap()
-				.closed
-				.store(true, std::sync::atomic::Ordering::Relaxed),
+			// The window may already be removed by a close from the game.
+			winit::event::WindowEvent::CloseRequested => {
+				if let Some(window) = self.windows.get(&window_id) {
+					window.closed.store(true, std::sync::atomic::Ordering::Relaxed);
+				}
+			}
 			_ => {}
 		}
 	}
@@ -296,6 +429,11 @@ impl<G: Game> ApplicationHandler<Event> for App<G> {
 						.collect(),
 				)
 			}
+			Event::CloseWindows(ids) => {
+				for id in ids {
+					self.windows.remove(&id);
+				}
+			}
 			Event::Exit => {
 				event_loop.exit();
 			}
@@ -0,0 +1,90 @@
+use std::{
+	marker::PhantomData,
+	sync::{
+		Arc,
+		atomic::{AtomicBool, Ordering},
+	},
+	thread::JoinHandle,
+};
+
+use super::{AppProxy, Game, Input, ProxyInner};
+
+#[derive(Debug, Default)]
+pub(super) struct HeadlessState {
+	/// Shared as the exit flag of every window.
+	pub(super) closed: Arc<AtomicBool>,
+	exited: AtomicBool,
+}
+impl HeadlessState {
+	pub(super) fn exit(&self) {
+		self.exited.store(true, Ordering::Relaxed);
+	}
+	pub(super) fn is_closed(&self) -> bool {
+		self.closed.load(Ordering::Relaxed) || self.exited.load(Ordering::Relaxed)
+	}
+}
+
+/// Runs a [Game] without an event loop, windows or rendering.
+///
+/// The game gets a headless [AppProxy]. Its [WindowSystem](super::WindowSystem)
+/// creates no windows and reports them closed once [HeadlessApp::close] or
+/// [AppProxy::exit] is called, and a [Runner](super::Runner) skips
+/// rendering. Input is set through [HeadlessApp::input] instead of device
+/// events.
+pub struct HeadlessApp<G: Game> {
+	game: Option<JoinHandle<()>>,
+	state: Arc<HeadlessState>,
+	input: Input,
+	_marker: PhantomData<G>,
+}
+impl<G: Game> Default for HeadlessApp<G> {
+	fn default() -> Self {
+		Self {
+			game: None,
+			state: Default::default(),
+			input: Default::default(),
+			_marker: PhantomData,
+		}
+	}
+}
+impl<G: Game> HeadlessApp<G> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	pub fn proxy(&self) -> AppProxy {
+		AppProxy {
+			inner: ProxyInner::Headless(self.state.clone()),
+			input: self.input.clone(),
+		}
+	}
+	pub fn input(&self) -> &Input {
+		&self.input
+	}
+	/// Creates and runs the game on its own thread, as [App](super::App) does
+	/// once resumed.
+	pub fn start(&mut self) {
+		if self.game.is_some() {
+			return;
+		}
+		let proxy = self.proxy();
+		self.game = Some(std::thread::spawn(move || {
+			let game = G::new(proxy);
+			game.run();
+		}));
+	}
+	/// Closes every window, as if the user did.
+	pub fn close(&self) {
+		self.state.closed.store(true, Ordering::Relaxed);
+	}
+	/// Whether the game called [AppProxy::exit].
+	pub fn has_exited(&self) -> bool {
+		self.state.exited.load(Ordering::Relaxed)
+	}
+	/// Waits for the game to return, without closing it.
+	pub fn join(mut self) -> std::thread::Result<()> {
+		match self.game.take() {
+			Some(game) => game.join(),
+			None => Ok(()),
+		}
+	}
+}
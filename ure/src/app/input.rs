@@ -27,6 +27,15 @@ pub struct PlayerActions {
 	pub actions: Vec<Actions>,
 }
 
+impl PlayerActions {
+	fn player_mut(&mut self, player: usize) -> &mut Actions {
+		if self.actions.len() <= player {
+			self.actions.resize_with(player + 1, Default::default);
+		}
+		&mut self.actions[player]
+	}
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Actions {
 	pub digital: BitVec,
@@ -55,6 +64,28 @@ impl Input {
 	pub fn set_input_map(&self, input_map: InputMap) {
 		*self.input_map.lock() = input_map;
 	}
+	/// Replaces every player's actions, for input not from devices.
+	pub fn set_player_actions(&self, player_actions: PlayerActions) {
+		*self.player_actions.lock() = player_actions;
+	}
+	/// Sets a digital action, adding players and actions up to it if missing.
+	pub fn inject_digital(&self, player: usize, action: usize, pressed: bool) {
+		let mut player_actions = self.player_actions.lock();
+		let actions = player_actions.player_mut(player);
+		if actions.digital.len() <= action {
+			actions.digital.resize(action + 1, false);
+		}
+		actions.set_digital(action, pressed);
+	}
+	/// Sets an analog action, adding players and actions up to it if missing.
+	pub fn inject_analog(&self, player: usize, action: usize, value: f64) {
+		let mut player_actions = self.player_actions.lock();
+		let actions = player_actions.player_mut(player);
+		if actions.analog.len() <= action {
+			actions.analog.resize(action + 1, 0.0);
+		}
+		actions.set_analog(action, value);
+	}
 	pub fn process_device_event(&self, device_id: &DeviceId, event: DeviceEvent) {
 		let input_map = self.input_map.lock();
 		let Some(&player) = input_map.device_map.get(device_id) else {
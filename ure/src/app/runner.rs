@@ -0,0 +1,239 @@
+use std::{
+	error::Error,
+	time::{Duration, Instant},
+};
+
+use spin_sleep::sleep;
+use ure_data::group::Data;
+use wgpu::{CommandEncoder, CommandEncoderDescriptor, PresentMode, TextureView};
+
+use super::{AppProxy, Time, WindowSystem};
+use crate::gpu::GPU;
+
+type Update<Key> = dyn FnMut(&Data<Key>) -> Result<(), Box<dyn Error>>;
+type Render<Key> = dyn FnMut(&Data<Key>, &mut CommandEncoder, &TextureView);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FramePacing {
+	/// As fast as possible, without vsync.
+	Uncapped,
+	/// Sleeps out the rest of each frame, without vsync. Rates that are not
+	/// positive and finite are uncapped.
+	Fps(f64),
+	/// Waits for the display on present. Headless, there is no display, so
+	/// frames are paced as [FramePacing::Fps] at 60 or at the fixed delta.
+	Vsync,
+}
+
+/// Owns the game loop: closes windows, updates, renders to every window and
+/// presents, until all windows are closed.
+///
+/// A [Time] resource is inserted into the [Data] if missing and updated at the
+/// start of each frame, from the wall clock or by [Runner::fixed_delta]. With a
+/// headless [AppProxy] nothing is rendered.
+pub struct Runner<Key: slotmap::Key> {
+	proxy: AppProxy,
+	window_system: WindowSystem<Key>,
+	pacing: FramePacing,
+	fixed_delta: Option<Duration>,
+	update: Vec<Box<Update<Key>>>,
+	fixed_update: Vec<Box<Update<Key>>>,
+	render: Vec<Box<Render<Key>>>,
+}
+impl<Key: slotmap::Key> Runner<Key> {
+	pub fn new(proxy: AppProxy, window_system: WindowSystem<Key>) -> Self {
+		Self {
+			proxy,
+			window_system,
+			pacing: FramePacing::Vsync,
+			fixed_delta: None,
+			update: Vec::new(),
+			fixed_update: Vec::new(),
+			render: Vec::new(),
+		}
+	}
+	pub fn pacing(mut self, pacing: FramePacing) -> Self {
+		self.pacing = pacing;
+		self
+	}
+	/// Steps [Time] by `delta` every frame instead of measuring it, see
+	/// [Time::update_with].
+	pub fn fixed_delta(mut self, delta: Duration) -> Self {
+		self.fixed_delta = Some(delta);
+		self
+	}
+	/// Runs once per frame, in the order added.
+	pub fn on_update(
+		mut self,
+		update: impl FnMut(&Data<Key>) -> Result<(), Box<dyn Error>> + 'static,
+	) -> Self {
+		self.update.push(Box::new(update));
+		self
+	}
+	/// Runs [Time::fixed_steps] times per frame, before [Self::on_update].
+	pub fn on_fixed_update(
+		mut self,
+		update: impl FnMut(&Data<Key>) -> Result<(), Box<dyn Error>> + 'static,
+	) -> Self {
+		self.fixed_update.push(Box::new(update));
+		self
+	}
+	/// Runs once per window each frame, with a view of its surface.
+	pub fn on_render(
+		mut self,
+		render: impl FnMut(&Data<Key>, &mut CommandEncoder, &TextureView) + 'static,
+	) -> Self {
+		self.render.push(Box::new(render));
+		self
+	}
+	/// Loops until every window is closed or an update fails, then exits the
+	/// app.
+	pub fn run(mut self, mut data: Data<Key>) -> Result<(), Box<dyn Error>> {
+		if data.resource::<Time>().is_none() {
+			data.insert_resource(Time::default());
+		}
+		self.window_system.set_present_mode(Some(match self.pacing {
+			FramePacing::Vsync => PresentMode::AutoVsync,
+			FramePacing::Uncapped | FramePacing::Fps(_) => PresentMode::AutoNoVsync,
+		}));
+		let result = self.run_frames(&data);
+		self.proxy.exit();
+		result
+	}
+	fn render(&mut self, data: &Data<Key>) {
+		self.window_system.reconfigure(data);
+		let mut encoder = GPU
+			.device
+			.create_command_encoder(&CommandEncoderDescriptor::default());
+		for view in self.window_system.views(data).iter() {
+			for render in self.render.iter_mut() {
+				render(data, &mut encoder, view);
+			}
+		}
+		GPU.queue.submit([encoder.finish()]);
+		self.window_system.present(data);
+	}
+	/// How long a frame lasts, [None] when not slept out.
+	fn frame_duration(&self) -> Option<Duration> {
+		match self.pacing {
+			FramePacing::Uncapped => None,
+			FramePacing::Fps(fps) => Duration::try_from_secs_f64(fps.recip()).ok(),
+			FramePacing::Vsync if self.proxy.is_headless() => Some(
+				self.fixed_delta
+					.unwrap_or(Duration::from_secs_f64(1.0 / 60.0)),
+			),
+			FramePacing::Vsync => None,
+		}
+	}
+	fn run_frames(&mut self, data: &Data<Key>) -> Result<(), Box<dyn Error>> {
+		loop {
+			let frame_start = Instant::now();
+			let fixed_steps = {
+				let mut time = data.resource_mut::<Time>().unwrap();
+				match self.fixed_delta {
+					Some(delta) => time.update_with(delta),
+					None => time.update(),
+				}
+				time.fixed_steps()
+			};
+
+			if self.window_system.close(data)? {
+				return Ok(());
+			}
+			for _ in 0..fixed_steps {
+				for update in self.fixed_update.iter_mut() {
+					update(data)?;
+				}
+			}
+			for update in self.update.iter_mut() {
+				update(data)?;
+			}
+
+			if !self.proxy.is_headless() {
+				self.render(data);
+			}
+
+			if let Some(frame) = self.frame_duration() {
+				sleep(frame.saturating_sub(frame_start.elapsed()));
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{cell::RefCell, rc::Rc};
+
+	use slotmap::DefaultKey;
+
+	use super::*;
+	use crate::app::{Game, HeadlessApp};
+
+	struct Idle;
+	impl Game for Idle {
+		fn new(_: AppProxy) -> Self {
+			Idle
+		}
+		fn run(self) {}
+	}
+
+	fn runner() -> Runner<DefaultKey> {
+		let proxy = HeadlessApp::<Idle>::new().proxy();
+		Runner::new(proxy.clone(), WindowSystem::new(proxy))
+	}
+
+	#[test]
+	fn fixed_delta_steps_time() {
+		let deltas = Rc::new(RefCell::new(Vec::new()));
+		let log = deltas.clone();
+		let proxy = HeadlessApp::<Idle>::new().proxy();
+		let exit = proxy.clone();
+		let mut data = Data::<DefaultKey>::new();
+		data.insert_resource(Time::new(Duration::from_millis(20), 8));
+		Runner::new(proxy.clone(), WindowSystem::new(proxy))
+			.pacing(FramePacing::Uncapped)
+			.fixed_delta(Duration::from_millis(30))
+			.on_update(move |data| {
+				let time = data.resource::<Time>().unwrap();
+				log.borrow_mut()
+					.push((time.raw_delta(), time.fixed_steps()));
+				if log.borrow().len() == 3 {
+					exit.exit();
+				}
+				Ok(())
+			})
+			.run(data)
+			.unwrap();
+		let millis = Duration::from_millis;
+		assert_eq!(
+			*deltas.borrow(),
+			[(millis(30), 1), (millis(30), 2), (millis(30), 1)]
+		);
+	}
+
+	#[test]
+	fn frame_duration() {
+		let fps = |fps| runner().pacing(FramePacing::Fps(fps)).frame_duration();
+		assert_eq!(fps(50.0), Some(Duration::from_millis(20)));
+		assert_eq!(fps(0.0), None);
+		assert_eq!(fps(-60.0), None);
+		assert_eq!(fps(f64::NAN), None);
+		assert_eq!(fps(f64::INFINITY), Some(Duration::ZERO));
+		assert_eq!(fps(1e-300), None);
+		assert_eq!(
+			runner().pacing(FramePacing::Uncapped).frame_duration(),
+			None
+		);
+	}
+
+	#[test]
+	fn headless_vsync_is_paced() {
+		let vsync = runner().pacing(FramePacing::Vsync);
+		assert_eq!(
+			vsync.frame_duration(),
+			Some(Duration::from_secs_f64(1.0 / 60.0))
+		);
+		let vsync = vsync.fixed_delta(Duration::from_millis(5));
+		assert_eq!(vsync.frame_duration(), Some(Duration::from_millis(5)));
+	}
+}
@@ -0,0 +1,187 @@
+use std::time::{Duration, Instant};
+
+use ure_data::schedule::FixedTimestep;
+
+/// Frame timing, stored as a [Data](ure_data::group::Data) resource and
+/// updated once per frame.
+///
+/// [Time::delta] is scaled and zero while paused, [Time::raw_delta] is not.
+/// Each update also decides how many fixed steps the frame runs, left over
+/// time is given by [Time::alpha] for blending between the last two steps.
+#[derive(Debug, Clone)]
+pub struct Time {
+	pub scale: f32,
+	pub paused: bool,
+	/// Weight of each new frame in the smoothed FPS, within `0..=1`.
+	pub smoothing: f32,
+	last: Option<Instant>,
+	frame: u64,
+	raw_delta: Duration,
+	delta: Duration,
+	elapsed: Duration,
+	smoothed: f32,
+	fixed: FixedTimestep,
+	fixed_steps: u32,
+}
+impl Default for Time {
+	fn default() -> Self {
+		Self::new(Duration::from_secs_f64(1.0 / 60.0), 8)
+	}
+}
+impl Time {
+	/// Runs at most `max_steps` fixed steps of `fixed_step` per frame.
+	pub fn new(fixed_step: Duration, max_steps: u32) -> Self {
+		Self {
+			scale: 1.0,
+			paused: false,
+			smoothing: 0.1,
+			last: None,
+			frame: 0,
+			raw_delta: Duration::ZERO,
+			delta: Duration::ZERO,
+			elapsed: Duration::ZERO,
+			smoothed: 0.0,
+			fixed: FixedTimestep::new(fixed_step, max_steps),
+			fixed_steps: 0,
+		}
+	}
+	/// Starts a frame, measuring the time since the last one. The first
+	/// update has a zero delta.
+	pub fn update(&mut self) {
+		let now = Instant::now();
+		let raw = self.last.map_or(Duration::ZERO, |last| now - last);
+		self.last = Some(now);
+		self.update_with(raw);
+	}
+	/// Starts a frame that took `raw` unscaled time, for stepping manually.
+	pub fn update_with(&mut self, raw: Duration) {
+		self.frame += 1;
+		self.raw_delta = raw;
+		let secs = raw.as_secs_f32();
+		self.smoothed = if self.smoothed == 0.0 {
+			secs
+		} else {
+			self.smoothed + (secs - self.smoothed) * self.smoothing
+		};
+
+		self.delta = if self.paused {
+			Duration::ZERO
+		} else {
+			raw.mul_f64(self.scale.max(0.0) as f64)
+		};
+		self.elapsed += self.delta;
+		self.fixed_steps = self.fixed.advance(self.delta);
+	}
+	/// Frames started so far.
+	pub fn frame(&self) -> u64 {
+		self.frame
+	}
+	pub fn delta(&self) -> Duration {
+		self.delta
+	}
+	pub fn delta_secs(&self) -> f32 {
+		self.delta.as_secs_f32()
+	}
+	pub fn raw_delta(&self) -> Duration {
+		self.raw_delta
+	}
+	/// Scaled time since the first frame.
+	pub fn elapsed(&self) -> Duration {
+		self.elapsed
+	}
+	/// Frames per second from the smoothed raw delta.
+	pub fn fps(&self) -> f32 {
+		if self.smoothed > 0.0 {
+			self.smoothed.recip()
+		} else {
+			0.0
+		}
+	}
+	pub fn fixed_step(&self) -> Duration {
+		self.fixed.step()
+	}
+	pub fn set_fixed_step(&mut self, fixed_step: Duration, max_steps: u32) {
+		self.fixed.set_step(fixed_step, max_steps);
+	}
+	/// Fixed steps to run this frame.
+	pub fn fixed_steps(&self) -> u32 {
+		self.fixed_steps
+	}
+	/// Progress towards the next fixed step, within `0..1`.
+	pub fn alpha(&self) -> f32 {
+		self.fixed.alpha()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use super::Time;
+
+	fn ms(ms: u64) -> Duration {
+		Duration::from_millis(ms)
+	}
+
+	#[test]
+	fn delta_is_scaled_and_paused() {
+		let mut time = Time::default();
+		time.update();
+		assert_eq!(time.raw_delta(), Duration::ZERO);
+
+		time.scale = 0.5;
+		time.update_with(ms(20));
+		assert_eq!(time.raw_delta(), ms(20));
+		assert_eq!(time.delta(), ms(10));
+		time.paused = true;
+		time.update_with(ms(20));
+		assert_eq!(time.raw_delta(), ms(20));
+		assert_eq!(time.delta(), Duration::ZERO);
+		time.paused = false;
+		time.scale = -1.0;
+		time.update_with(ms(20));
+		assert_eq!(time.delta(), Duration::ZERO);
+		assert_eq!(time.elapsed(), ms(10));
+		assert_eq!(time.frame(), 4);
+	}
+
+	#[test]
+	fn fps_is_smoothed() {
+		let mut time = Time::default();
+		assert_eq!(time.fps(), 0.0);
+		time.smoothing = 0.5;
+		time.update_with(ms(10));
+		assert!((time.fps() - 100.0).abs() < 1e-3);
+		time.update_with(ms(30));
+		assert!((time.fps() - 50.0).abs() < 1e-3);
+		// Unaffected by scale and pause.
+		time.paused = true;
+		time.update_with(ms(20));
+		assert!((time.fps() - 50.0).abs() < 1e-3);
+	}
+
+	#[test]
+	fn fixed_steps_follow_the_scaled_delta() {
+		let mut time = Time::new(ms(10), 3);
+		time.update_with(ms(25));
+		assert_eq!(time.fixed_steps(), 2);
+		assert!((time.alpha() - 0.5).abs() < 1e-6);
+		time.scale = 2.0;
+		time.update_with(ms(10));
+		assert_eq!(time.fixed_steps(), 2);
+		assert!((time.alpha() - 0.5).abs() < 1e-6);
+		time.paused = true;
+		time.update_with(ms(10));
+		assert_eq!(time.fixed_steps(), 0);
+		time.paused = false;
+		time.update_with(ms(100));
+		assert_eq!(time.fixed_steps(), 3);
+		assert_eq!(time.alpha(), 0.0);
+
+		time.set_fixed_step(ms(5), 8);
+		assert_eq!(time.fixed_step(), ms(5));
+		time.scale = 1.0;
+		time.update_with(ms(20));
+		assert_eq!(time.fixed_steps(), 4);
+	}
+}
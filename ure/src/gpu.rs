@@ -1,10 +1,10 @@
-use std::cell::{Ref, RefMut};
 use std::ops::{Deref, DerefMut};
 use std::{marker::PhantomData, sync::OnceLock};
 
 use bitvec::slice::BitSlice;
 use bitvec::vec::BitVec;
 use bytemuck::Pod;
+use ure_data::cell::{MaybeSync, Ref, RefMut};
 use ure_data::containers::{Container, NewDefault, NewWith};
 use wgpu::{
 	Adapter, Buffer, BufferUsages, CommandEncoder, Device, DeviceDescriptor, Instance,
@@ -101,11 +101,11 @@ impl<T: Pod> TypedBuffer<T> {
 		self.len
 	}
 }
-impl<T: Pod> Container for TypedBuffer<T> {
+impl<T: Pod + MaybeSync> Container for TypedBuffer<T> {
 	type Ref<'a> = (TypedBufferView<T>, Ref<'a, BitSlice>);
 	type RefMut<'a> = (TypedBufferViewMut<T>, RefMut<'a, BitSlice>);
 
-	fn as_ref<'a>(cont: std::cell::Ref<'a, Self>) -> Self::Ref<'a> {
+	fn as_ref<'a>(cont: Ref<'a, Self>) -> Self::Ref<'a> {
 		(
 			TypedBufferView {
 				buffer: cont.inner.clone(),
@@ -115,7 +115,7 @@ impl<T: Pod> Container for TypedBuffer<T> {
 			Ref::map(cont, |c| c.diff.as_bitslice()),
 		)
 	}
-	fn as_mut<'a>(cont: std::cell::RefMut<'a, Self>) -> Self::RefMut<'a> {
+	fn as_mut<'a>(cont: RefMut<'a, Self>) -> Self::RefMut<'a> {
 		(
 			TypedBufferViewMut {
 				buffer: cont.inner.clone(),
@@ -133,14 +133,22 @@ impl<T: Pod> Container for TypedBuffer<T> {
 		self.up_len -= indices.len();
 		self.diff.truncate(self.up_len);
 	}
+	fn clear(&mut self, _: usize) {
+		self.up_len = 0;
+		self.diff.clear();
+	}
+	/// Every row is rewritten on the next update.
+	fn permute(&mut self, _: &[usize]) {
+		self.diff.fill(true);
+	}
 }
-impl<T: Pod + Default> NewDefault for TypedBuffer<T> {
+impl<T: Pod + Default + MaybeSync> NewDefault for TypedBuffer<T> {
 	fn new_default(&mut self, num: usize) {
 		self.up_len += num;
 		self.diff.resize(self.up_len, true);
 	}
 }
-impl<T: Pod + Default> NewWith for TypedBuffer<T> {
+impl<T: Pod + Default + MaybeSync> NewWith for TypedBuffer<T> {
 	type Args = ();
 
 	fn new_with(&mut self, _: Self::Args) {
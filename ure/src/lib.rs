@@ -1,3 +1,7 @@
+pub mod animation;
 pub mod app;
 pub mod gpu;
+#[cfg(feature = "physics2d")]
+pub mod physics2d;
+pub mod spatial;
 pub mod two;
\ No newline at end of file
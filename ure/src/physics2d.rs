@@ -0,0 +1,629 @@
+//! Rigid bodies over [Transforms2D].
+//!
+//! Bodies move by [Velocities2D] and collide by [Colliders2D]. Only
+//! translation is simulated, rotation and scale of the transform shape the
+//! collider but are never changed. Bodies with an infinite, zero or negative
+//! [Masses2D] are static. Nothing here touches the GPU.
+
+use std::{error::Error, time::Duration};
+
+use glam::{Affine2, Vec2};
+use itertools::izip;
+use ure_data::{
+	component,
+	components::{MissingDependency, NewArgs},
+	glob::{CompMut, CompRef, ContMut},
+	group::Data,
+	schedule::FixedTimestep,
+	signal,
+};
+
+use crate::{
+	spatial::{Aabb2, Grid2D},
+	two::Transforms2D,
+};
+
+component!(pub Velocities2D: Vec<Vec2>, new_velocities_2d, Vec<Vec2>);
+pub fn new_velocities_2d(ContMut(mut velocities): ContMut<Velocities2D>, args: &mut NewArgs) {
+	if let Some(new_velocities) = args.take::<Velocities2D>() {
+		velocities.extend(new_velocities);
+	} else {
+		velocities.extend(vec![Vec2::ZERO; args.len()]);
+	}
+}
+component!(pub Masses2D: Vec<f32>, new_masses_2d, Vec<f32>);
+pub fn new_masses_2d(ContMut(mut masses): ContMut<Masses2D>, args: &mut NewArgs) {
+	if let Some(new_masses) = args.take::<Masses2D>() {
+		masses.extend(new_masses);
+	} else {
+		masses.extend(vec![1.0; args.len()]);
+	}
+}
+component!(pub Colliders2D: Vec<Collider2D>, new_colliders_2d, Vec<Collider2D>);
+pub fn new_colliders_2d(ContMut(mut colliders): ContMut<Colliders2D>, args: &mut NewArgs) {
+	if let Some(new_colliders) = args.take::<Colliders2D>() {
+		colliders.extend(new_colliders);
+	} else {
+		colliders.extend(vec![Collider2D::default(); args.len()]);
+	}
+}
+
+/// Collision shape in the space of the body's transform.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Collider2D {
+	Circle(f32),
+	/// Box from its half size, rotated with the transform.
+	Box(Vec2),
+	/// Convex polygon, counter clockwise.
+	Polygon(Vec<Vec2>),
+}
+impl Default for Collider2D {
+	/// The unit quad drawn by [Visuals2D](crate::two::Visuals2D).
+	fn default() -> Self {
+		Self::Box(Vec2::splat(0.5))
+	}
+}
+impl Collider2D {
+	fn shape(&self, transform: &Affine2) -> Shape {
+		match self {
+			Self::Circle(radius) => Shape::Circle {
+				center: transform.translation,
+				radius: radius
+					* transform
+						.matrix2
+						.x_axis
+						.length()
+						.max(transform.matrix2.y_axis.length()),
+			},
+			Self::Box(half) => Shape::polygon(
+				transform,
+				[
+					Vec2::new(-half.x, -half.y),
+					Vec2::new(half.x, -half.y),
+					Vec2::new(half.x, half.y),
+					Vec2::new(-half.x, half.y),
+				]
+				.into_iter(),
+			),
+			Self::Polygon(points) => Shape::polygon(transform, points.iter().copied()),
+		}
+	}
+}
+
+/// Collider in world space.
+enum Shape {
+	Circle { center: Vec2, radius: f32 },
+	Polygon(Vec<Vec2>),
+}
+impl Shape {
+	fn polygon(transform: &Affine2, points: impl Iterator<Item = Vec2>) -> Self {
+		let mut points: Vec<Vec2> = points.map(|p| transform.transform_point2(p)).collect();
+		// Mirroring transforms flip the winding.
+		if transform.matrix2.determinant() < 0.0 {
+			points.reverse();
+		}
+		Self::Polygon(points)
+	}
+	fn bounds(&self) -> Aabb2 {
+		match self {
+			Self::Circle { center, radius } => Aabb2::from_center(*center, Vec2::splat(*radius)),
+			Self::Polygon(points) => Aabb2::new(
+				points.iter().copied().reduce(Vec2::min).unwrap_or_default(),
+				points.iter().copied().reduce(Vec2::max).unwrap_or_default(),
+			),
+		}
+	}
+}
+
+/// Contact manifold between rows `a` and `b` of a group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contact2D {
+	pub a: usize,
+	pub b: usize,
+	/// Points from `a` to `b`.
+	pub normal: Vec2,
+	pub depth: f32,
+	/// One or two points where the bodies touch.
+	pub points: Vec<Vec2>,
+}
+
+signal!(pub COLLISION_2D: &[Contact2D]);
+
+#[derive(Debug, Clone, Copy)]
+pub struct PhysicsSettings2D {
+	pub step: Duration,
+	/// Steps run at most per update, time beyond is dropped.
+	pub max_steps: u32,
+	pub gravity: Vec2,
+	pub restitution: f32,
+	pub friction: f32,
+	/// Passes over the contacts when resolving velocities.
+	pub iterations: u32,
+	/// Cell size of the broad phase grid.
+	pub cell_size: f32,
+}
+impl Default for PhysicsSettings2D {
+	fn default() -> Self {
+		Self {
+			step: Duration::from_secs_f64(1.0 / 60.0),
+			max_steps: 8,
+			gravity: Vec2::new(0.0, -9.81),
+			restitution: 0.2,
+			friction: 0.4,
+			iterations: 4,
+			cell_size: 1.0,
+		}
+	}
+}
+
+/// Advances bodies by one step, leaving the contacts found in `contacts`.
+pub fn step_physics_2d(
+	CompMut((mut transforms, mut velocities)): CompMut<(Transforms2D, Velocities2D)>,
+	CompRef((masses, colliders)): CompRef<(Masses2D, Colliders2D)>,
+	(settings, contacts): &mut (PhysicsSettings2D, Vec<Contact2D>),
+) {
+	let dt = settings.step.as_secs_f32();
+	contacts.clear();
+
+	// Zero for static bodies.
+	let inverse: Vec<f32> = masses
+		.iter()
+		.map(|&mass| if mass > 0.0 { mass.recip() } else { 0.0 })
+		.collect();
+
+	// Semi implicit Euler.
+	for (transform, velocity, inverse) in
+		izip!(transforms.iter_mut(), velocities.iter_mut(), inverse.iter())
+	{
+		if *inverse > 0.0 {
+			*velocity += settings.gravity * dt;
+		}
+		transform.translation += *velocity * dt;
+	}
+	let shapes: Vec<Shape> = izip!(colliders.iter(), transforms.iter())
+		.map(|(collider, transform)| collider.shape(transform))
+		.collect();
+	let mut grid = Grid2D::new(settings.cell_size);
+	for shape in shapes.iter() {
+		grid.push(shape.bounds());
+	}
+	for (a, shape) in shapes.iter().enumerate() {
+		for b in grid.query_aabb(&grid.bounds()[a]) {
+			if b <= a || inverse[a] + inverse[b] == 0.0 {
+				continue;
+			}
+			if let Some(contact) = collide(shape, &shapes[b]) {
+				contacts.push(Contact2D { a, b, ..contact });
+			}
+		}
+	}
+
+	for _ in 0..settings.iterations {
+		for contact in contacts.iter() {
+			let (a, b, normal) = (contact.a, contact.b, contact.normal);
+			let total = inverse[a] + inverse[b];
+			let relative = velocities[b] - velocities[a];
+			let speed = relative.dot(normal);
+			if speed > 0.0 {
+				continue;
+			}
+			let j = -(1.0 + settings.restitution) * speed / total;
+			velocities[a] -= normal * j * inverse[a];
+			velocities[b] += normal * j * inverse[b];
+
+			let relative = velocities[b] - velocities[a];
+			let tangent = (relative - normal * relative.dot(normal)).normalize_or_zero();
+			let limit = j * settings.friction;
+			let jt = (-relative.dot(tangent) / total).clamp(-limit, limit);
+			velocities[a] -= tangent * jt * inverse[a];
+			velocities[b] += tangent * jt * inverse[b];
+		}
+	}
+
+	// Push overlapping bodies apart so resting contacts do not sink.
+	const SLOP: f32 = 0.005;
+	const PERCENT: f32 = 0.8;
+	for contact in contacts.iter() {
+		let (a, b) = (contact.a, contact.b);
+		let correction =
+			contact.normal * (contact.depth - SLOP).max(0.0) * PERCENT / (inverse[a] + inverse[b]);
+		transforms[a].translation -= correction * inverse[a];
+		transforms[b].translation += correction * inverse[b];
+	}
+}
+
+fn collide(a: &Shape, b: &Shape) -> Option<Contact2D> {
+	match (a, b) {
+		(
+			Shape::Circle {
+				center: ca,
+				radius: ra,
+			},
+			Shape::Circle {
+				center: cb,
+				radius: rb,
+			},
+		) => circle_circle(*ca, *ra, *cb, *rb),
+		(Shape::Polygon(a), Shape::Circle { center, radius }) => {
+			polygon_circle(a, *center, *radius)
+		}
+		(Shape::Circle { center, radius }, Shape::Polygon(b)) => {
+			polygon_circle(b, *center, *radius).map(|c| Contact2D {
+				normal: -c.normal,
+				..c
+			})
+		}
+		(Shape::Polygon(a), Shape::Polygon(b)) => polygon_polygon(a, b),
+	}
+}
+
+fn manifold(normal: Vec2, depth: f32, points: Vec<Vec2>) -> Contact2D {
+	Contact2D {
+		a: 0,
+		b: 0,
+		normal,
+		depth,
+		points,
+	}
+}
+
+fn circle_circle(ca: Vec2, ra: f32, cb: Vec2, rb: f32) -> Option<Contact2D> {
+	let offset = cb - ca;
+	let distance = offset.length();
+	if distance > ra + rb {
+		return None;
+	}
+	let normal = if distance > 0.0 {
+		offset / distance
+	} else {
+		Vec2::Y
+	};
+	Some(manifold(normal, ra + rb - distance, vec![ca + normal * ra]))
+}
+
+/// Outward normal of the edge starting at `i`.
+fn edge_normal(polygon: &[Vec2], i: usize) -> Vec2 {
+	let edge = polygon[(i + 1) % polygon.len()] - polygon[i];
+	Vec2::new(edge.y, -edge.x).normalize_or_zero()
+}
+
+/// Normal points from the polygon to the circle.
+fn polygon_circle(polygon: &[Vec2], center: Vec2, radius: f32) -> Option<Contact2D> {
+	let (edge, separation) = (0..polygon.len())
+		.map(|i| (i, edge_normal(polygon, i).dot(center - polygon[i])))
+		.max_by(|a, b| a.1.total_cmp(&b.1))?;
+	if separation > radius {
+		return None;
+	}
+	if separation <= 0.0 {
+		// Center inside, push out through the nearest face.
+		let normal = edge_normal(polygon, edge);
+		return Some(manifold(
+			normal,
+			radius - separation,
+			vec![center - normal * radius],
+		));
+	}
+	let v1 = polygon[edge];
+	let v2 = polygon[(edge + 1) % polygon.len()];
+	let along = v2 - v1;
+	let t = ((center - v1).dot(along) / along.length_squared()).clamp(0.0, 1.0);
+	let closest = v1 + along * t;
+	let offset = center - closest;
+	let distance = offset.length();
+	if distance > radius {
+		return None;
+	}
+	Some(manifold(
+		offset / distance,
+		radius - distance,
+		vec![closest],
+	))
+}
+
+/// Edge of `a` most separating it from `b`, and that separation.
+fn max_separation(a: &[Vec2], b: &[Vec2]) -> (usize, f32) {
+	(0..a.len())
+		.map(|i| {
+			let normal = edge_normal(a, i);
+			let separation = b
+				.iter()
+				.map(|p| normal.dot(*p - a[i]))
+				.fold(f32::INFINITY, f32::min);
+			(i, separation)
+		})
+		.max_by(|a, b| a.1.total_cmp(&b.1))
+		.unwrap_or((0, f32::INFINITY))
+}
+
+/// Keeps the part of the segment where `normal.dot(p) <= offset`.
+fn clip(points: [Vec2; 2], normal: Vec2, offset: f32) -> Option<[Vec2; 2]> {
+	let d0 = normal.dot(points[0]) - offset;
+	let d1 = normal.dot(points[1]) - offset;
+	if d0 > 0.0 && d1 > 0.0 {
+		return None;
+	}
+	if d0 <= 0.0 && d1 <= 0.0 {
+		return Some(points);
+	}
+	let mid = points[0] + (points[1] - points[0]) * (d0 / (d0 - d1));
+	Some(if d0 > 0.0 {
+		[mid, points[1]]
+	} else {
+		[points[0], mid]
+	})
+}
+
+/// Separating axis test, then the incident edge clipped against the
+/// reference face for up to two points.
+fn polygon_polygon(a: &[Vec2], b: &[Vec2]) -> Option<Contact2D> {
+	let (edge_a, separation_a) = max_separation(a, b);
+	if separation_a > 0.0 {
+		return None;
+	}
+	let (edge_b, separation_b) = max_separation(b, a);
+	if separation_b > 0.0 {
+		return None;
+	}
+	// Prefer `a` as reference so the choice does not flicker between frames.
+	let (reference, incident, edge, flip) = if separation_b > separation_a + 1e-3 {
+		(b, a, edge_b, true)
+	} else {
+		(a, b, edge_a, false)
+	};
+	let normal = edge_normal(reference, edge);
+	let incident_edge = (0..incident.len())
+		.min_by(|&i, &j| {
+			edge_normal(incident, i)
+				.dot(normal)
+				.total_cmp(&edge_normal(incident, j).dot(normal))
+		})
+		.unwrap();
+	let points = [
+		incident[incident_edge],
+		incident[(incident_edge + 1) % incident.len()],
+	];
+
+	let v1 = reference[edge];
+	let v2 = reference[(edge + 1) % reference.len()];
+	let tangent = (v2 - v1).normalize_or_zero();
+	let points = clip(points, -tangent, -tangent.dot(v1))?;
+	let points = clip(points, tangent, tangent.dot(v2))?;
+
+	let mut depth: f32 = 0.0;
+	let mut contact = Vec::with_capacity(2);
+	for p in points {
+		let separation = normal.dot(p - v1);
+		if separation <= 0.0 {
+			depth = depth.max(-separation);
+			contact.push(p);
+		}
+	}
+	if contact.is_empty() {
+		return None;
+	}
+	Some(manifold(
+		if flip { -normal } else { normal },
+		depth,
+		contact,
+	))
+}
+
+/// Steps the bodies of groups at a fixed rate.
+pub struct Physics2D<Key: slotmap::Key> {
+	keys: Vec<Key>,
+	pub settings: PhysicsSettings2D,
+	timestep: FixedTimestep,
+	contacts: Vec<Contact2D>,
+}
+impl<Key: slotmap::Key> Physics2D<Key> {
+	pub fn new(settings: PhysicsSettings2D) -> Self {
+		assert!(!settings.step.is_zero(), "Physics step is zero.");
+		Self {
+			keys: Vec::new(),
+			timestep: FixedTimestep::new(settings.step, settings.max_steps),
+			settings,
+			contacts: Vec::new(),
+		}
+	}
+	/// Adds the body components the group does not have yet, keeping those
+	/// it has. Rows the group already has get the defaults of new rows.
+	pub fn add(&mut self, data: &Data<Key>, key: Key) -> Result<(), MissingDependency> {
+		let Some(group) = data.get(key) else {
+			return Ok(());
+		};
+		let mut group = group.borrow_mut();
+		let len = group.len();
+		if !group.contains_component::<Transforms2D>() {
+			group.add_container::<Transforms2D>(vec![Affine2::IDENTITY; len])?;
+		}
+		if !group.contains_component::<Velocities2D>() {
+			group.add_container::<Velocities2D>(vec![Vec2::ZERO; len])?;
+		}
+		if !group.contains_component::<Masses2D>() {
+			group.add_container::<Masses2D>(vec![1.0; len])?;
+		}
+		if !group.contains_component::<Colliders2D>() {
+			group.add_container::<Colliders2D>(vec![Collider2D::default(); len])?;
+		}
+		if !self.keys.contains(&key) {
+			self.keys.push(key);
+		}
+		Ok(())
+	}
+	/// Runs as many steps as `delta` allows.
+	pub fn update(&mut self, data: &Data<Key>, delta: Duration) -> Result<(), Box<dyn Error>> {
+		self.timestep
+			.set_step(self.settings.step, self.settings.max_steps);
+		for _ in 0..self.timestep.advance(delta) {
+			self.step(data)?;
+		}
+		Ok(())
+	}
+	/// Runs one step on every group, then [COLLISION_2D] on groups with
+	/// contacts, whose methods see the [Data]'s resources.
+	pub fn step(&mut self, data: &Data<Key>) -> Result<(), Box<dyn Error>> {
+		for key in self.keys.iter() {
+			let Some(mut globule) = data.globule_mut(*key) else {
+				continue;
+			};
+			let mut args = (self.settings, std::mem::take(&mut self.contacts));
+			globule.as_ref().call_method(step_physics_2d, &mut args)?;
+			self.contacts = args.1;
+			if !self.contacts.is_empty() {
+				globule.call_signal(&COLLISION_2D, self.contacts.as_slice())?;
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use slotmap::DefaultKey;
+	use ure_data::group::Group;
+
+	use super::*;
+
+	fn shape(collider: Collider2D, x: f32, y: f32) -> Shape {
+		collider.shape(&Affine2::from_translation(Vec2::new(x, y)))
+	}
+
+	fn assert_near(a: Vec2, b: Vec2) {
+		assert!(a.abs_diff_eq(b, 1e-5), "{a} is not {b}");
+	}
+
+	/// Contact points sorted by x then y.
+	fn points(contact: &Contact2D) -> Vec<Vec2> {
+		let mut points = contact.points.clone();
+		points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+		points
+	}
+
+	#[test]
+	fn circle_circle_manifold() {
+		let a = shape(Collider2D::Circle(1.0), 0.0, 0.0);
+		let b = shape(Collider2D::Circle(1.0), 1.5, 0.0);
+		let contact = collide(&a, &b).unwrap();
+		assert_near(contact.normal, Vec2::X);
+		assert!((contact.depth - 0.5).abs() < 1e-5);
+		assert_eq!(contact.points, [Vec2::X]);
+		let far = shape(Collider2D::Circle(1.0), 2.5, 0.0);
+		assert!(collide(&a, &far).is_none());
+	}
+
+	#[test]
+	fn box_box_manifold() {
+		let a = shape(Collider2D::default(), 0.0, 0.0);
+		let b = shape(Collider2D::default(), 0.8, 0.25);
+		let contact = collide(&a, &b).unwrap();
+		assert_near(contact.normal, Vec2::X);
+		assert!((contact.depth - 0.2).abs() < 1e-5);
+		let points = points(&contact);
+		assert_eq!(points.len(), 2);
+		assert_near(points[0], Vec2::new(0.3, -0.25));
+		assert_near(points[1], Vec2::new(0.3, 0.5));
+		// From `b`, the normal flips.
+		assert_near(collide(&b, &a).unwrap().normal, -Vec2::X);
+		let far = shape(Collider2D::default(), 1.1, 0.0);
+		assert!(collide(&a, &far).is_none());
+	}
+
+	#[test]
+	fn polygon_circle_manifold() {
+		let triangle = Collider2D::Polygon(vec![
+			Vec2::new(0.0, 0.0),
+			Vec2::new(2.0, 0.0),
+			Vec2::new(1.0, 2.0),
+		]);
+		let polygon = shape(triangle, 0.0, 0.0);
+		let below = shape(Collider2D::Circle(1.0), 1.0, -0.5);
+		let contact = collide(&polygon, &below).unwrap();
+		assert_near(contact.normal, -Vec2::Y);
+		assert!((contact.depth - 0.5).abs() < 1e-5);
+		assert_eq!(contact.points, [Vec2::X]);
+		assert_near(collide(&below, &polygon).unwrap().normal, Vec2::Y);
+		// Center inside, pushed out through the nearest face.
+		let inside = shape(Collider2D::Circle(0.5), 1.0, 0.2);
+		let contact = collide(&polygon, &inside).unwrap();
+		assert_near(contact.normal, -Vec2::Y);
+		assert!((contact.depth - 0.7).abs() < 1e-5);
+		let far = shape(Collider2D::Circle(0.5), 1.0, -1.0);
+		assert!(collide(&polygon, &far).is_none());
+	}
+
+	#[test]
+	fn boxes_stack_on_static_ground() {
+		let mut data = Data::<DefaultKey>::new();
+		let key = data.insert(Group::default().into());
+		let mut physics = Physics2D::new(PhysicsSettings2D::default());
+		physics.add(&data, key).unwrap();
+		{
+			let mut group = data[key].borrow_mut();
+			group
+				.new(3)
+				// A zero mass is static, like an infinite one.
+				.with::<Masses2D>(vec![0.0, 1.0, 1.0])
+				.with::<Colliders2D>(vec![
+					Collider2D::Box(Vec2::new(5.0, 0.5)),
+					Collider2D::default(),
+					Collider2D::default(),
+				])
+				.done()
+				.unwrap();
+			let mut transforms = group.borrow_container_mut::<Transforms2D>().unwrap();
+			for (transform, y) in transforms.iter_mut().zip([-0.5, 0.5, 1.5]) {
+				transform.translation.y = y;
+			}
+		}
+		for _ in 0..240 {
+			physics.step(&data).unwrap();
+		}
+		let group = data[key].borrow();
+		let transforms = group.borrow_container::<Transforms2D>().unwrap();
+		let velocities = group.borrow_container::<Velocities2D>().unwrap();
+		assert_eq!(transforms[0].translation, Vec2::new(0.0, -0.5));
+		assert_eq!(velocities[0], Vec2::ZERO);
+		// Resting contacts keep some overlap, see SLOP.
+		for (row, y) in [(1, 0.5), (2, 1.5)] {
+			let translation = transforms[row].translation;
+			assert!(translation.is_finite() && velocities[row].is_finite());
+			assert!(
+				translation.x.abs() < 1e-3,
+				"row {row} slid to {translation}"
+			);
+			assert!(
+				(translation.y - y).abs() < 0.05,
+				"row {row} rests at {translation}"
+			);
+			assert!(
+				velocities[row].length() < 0.2,
+				"row {row} moves at {}",
+				velocities[row]
+			);
+		}
+		let gap = transforms[2].translation.y - transforms[1].translation.y;
+		assert!((gap - 1.0).abs() < 0.05, "boxes {gap} apart");
+	}
+
+	#[test]
+	fn add_keeps_the_components_a_group_has() {
+		let mut data = Data::<DefaultKey>::new();
+		let mut group = Group::default();
+		group.add_component::<Transforms2D>().unwrap();
+		group.new(2).done().unwrap();
+		let key = data.insert(group.into());
+		let mut physics = Physics2D::new(PhysicsSettings2D::default());
+		physics.add(&data, key).unwrap();
+		physics.add(&data, key).unwrap();
+		assert_eq!(physics.keys, [key]);
+		let mut group = data[key].borrow_mut();
+		assert_eq!(group.borrow_container::<Transforms2D>().unwrap().len(), 2);
+		assert_eq!(*group.borrow_container::<Masses2D>().unwrap(), [1.0; 2]);
+		group.new(1).done().unwrap();
+		assert_eq!(group.len(), 3);
+		assert_eq!(group.borrow_container::<Transforms2D>().unwrap().len(), 3);
+		assert_eq!(group.borrow_container::<Velocities2D>().unwrap().len(), 3);
+		assert_eq!(group.borrow_container::<Colliders2D>().unwrap().len(), 3);
+	}
+}
@@ -0,0 +1,426 @@
+//! Spatial queries over [Transforms2D].
+//!
+//! [Spatial2D] keeps the bounds of every row in a uniform grid. Being a
+//! container, it follows rows through deletes, clears and permutes on its
+//! own. Moved transforms are picked up by [update_spatial_2d].
+
+use glam::{Affine2, IVec2, Vec2};
+use rustc_hash::FxHashMap;
+use ure_data::{
+	cell::{Ref, RefMut},
+	component,
+	components::NewArgs,
+	containers::{Container, permute_by_swaps},
+	glob::{CompRef, ContMut},
+};
+
+use crate::two::Transforms2D;
+
+/// Axis aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Aabb2 {
+	pub min: Vec2,
+	pub max: Vec2,
+}
+impl Aabb2 {
+	pub const fn new(min: Vec2, max: Vec2) -> Self {
+		Self { min, max }
+	}
+	pub fn from_center(center: Vec2, half_size: Vec2) -> Self {
+		Self {
+			min: center - half_size,
+			max: center + half_size,
+		}
+	}
+	/// Bounds of the unit quad drawn by [Visuals2D](crate::two::Visuals2D)
+	/// under `transform`.
+	pub fn of_transform(transform: &Affine2) -> Self {
+		let half = (transform.matrix2.x_axis.abs() + transform.matrix2.y_axis.abs()) * 0.5;
+		Self::from_center(transform.translation, half)
+	}
+	pub fn intersects(&self, other: &Aabb2) -> bool {
+		self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+	}
+	pub fn contains(&self, point: Vec2) -> bool {
+		self.min.cmple(point).all() && point.cmple(self.max).all()
+	}
+	/// Squared distance from `point` to the closest point of the box.
+	pub fn distance_squared(&self, point: Vec2) -> f32 {
+		point.clamp(self.min, self.max).distance_squared(point)
+	}
+	/// Distance along the ray to where it enters the box, if it does before
+	/// `max`.
+	pub fn ray(&self, origin: Vec2, dir: Vec2, max: f32) -> Option<f32> {
+		let (near, far) = self.slabs(origin, dir)?;
+		let near = near.max(0.0);
+		(near <= far.min(max)).then_some(near)
+	}
+	/// Distances along the ray to where it enters and leaves the box on both
+	/// axes, [None] if it runs parallel to an axis outside the box.
+	fn slabs(&self, origin: Vec2, dir: Vec2) -> Option<(f32, f32)> {
+		let mut near = f32::NEG_INFINITY;
+		let mut far = f32::INFINITY;
+		for axis in 0..2 {
+			if dir[axis] == 0.0 {
+				// Dividing would give 0 * inf = NaN for origins on a face.
+				if origin[axis] < self.min[axis] || origin[axis] > self.max[axis] {
+					return None;
+				}
+				continue;
+			}
+			let a = (self.min[axis] - origin[axis]) / dir[axis];
+			let b = (self.max[axis] - origin[axis]) / dir[axis];
+			near = near.max(a.min(b));
+			far = far.min(a.max(b));
+		}
+		Some((near, far))
+	}
+}
+
+/// Uniform grid of row bounds.
+///
+/// Rows are stored in every cell their bounds overlap, so cells should be
+/// about the size of the usual row.
+#[derive(Debug)]
+pub struct Grid2D {
+	cell_size: f32,
+	bounds: Vec<Aabb2>,
+	cells: FxHashMap<IVec2, Vec<usize>>,
+}
+impl Default for Grid2D {
+	fn default() -> Self {
+		Self::new(1.0)
+	}
+}
+impl Grid2D {
+	pub fn new(cell_size: f32) -> Self {
+		assert!(cell_size > 0.0, "Cell size must be positive.");
+		Self {
+			cell_size,
+			bounds: Vec::new(),
+			cells: FxHashMap::default(),
+		}
+	}
+	pub fn cell_size(&self) -> f32 {
+		self.cell_size
+	}
+	pub fn len(&self) -> usize {
+		self.bounds.len()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.bounds.is_empty()
+	}
+	pub fn bounds(&self) -> &[Aabb2] {
+		&self.bounds
+	}
+	/// Adds a row after the last one.
+	pub fn push(&mut self, bounds: Aabb2) {
+		let row = self.bounds.len();
+		self.bounds.push(bounds);
+		self.insert(row);
+	}
+	/// Moves `row` to `bounds`, only touching cells if its cell range changed.
+	pub fn set(&mut self, row: usize, bounds: Aabb2) {
+		let old = self.bounds[row];
+		if old == bounds {
+			return;
+		}
+		if self.cell_range(&old) == self.cell_range(&bounds) {
+			self.bounds[row] = bounds;
+			return;
+		}
+		self.remove(row);
+		self.bounds[row] = bounds;
+		self.insert(row);
+	}
+
+	/// Rows whose bounds intersect `aabb`, sorted.
+	pub fn query_aabb(&self, aabb: &Aabb2) -> Vec<usize> {
+		let mut rows = self.candidates(aabb);
+		rows.retain(|&row| self.bounds[row].intersects(aabb));
+		rows
+	}
+	/// Rows whose bounds are within `radius` of `center`, sorted.
+	pub fn query_radius(&self, center: Vec2, radius: f32) -> Vec<usize> {
+		let mut rows = self.candidates(&Aabb2::from_center(center, Vec2::splat(radius)));
+		rows.retain(|&row| self.bounds[row].distance_squared(center) <= radius * radius);
+		rows
+	}
+	/// Rows whose bounds the ray hits before `max`, with the distance to each
+	/// hit, nearest first. `dir` should be normalized for distances to be in
+	/// world units.
+	pub fn query_ray(&self, origin: Vec2, dir: Vec2, max: f32) -> Vec<(usize, f32)> {
+		let Some((near, far)) = self.clip_ray(origin, dir, max) else {
+			return Vec::new();
+		};
+		// Walk the cells the ray crosses inside the bounds of every row.
+		let start = origin + dir * near;
+		let mut cell = self.cell(start);
+		let steps = (self.cell(origin + dir * far) - cell).abs().element_sum();
+		let step = dir.signum().as_ivec2();
+		let next_edge = (cell + step.max(IVec2::ZERO)).as_vec2() * self.cell_size;
+		let parallel = dir.cmpeq(Vec2::ZERO);
+		let mut t = Vec2::select(parallel, Vec2::INFINITY, (next_edge - start) / dir);
+		let delta = Vec2::select(parallel, Vec2::INFINITY, self.cell_size / dir.abs());
+		let mut rows: Vec<usize> = Vec::new();
+		for _ in 0..=steps {
+			if let Some(cell_rows) = self.cells.get(&cell) {
+				rows.extend(cell_rows);
+			}
+			if t.x < t.y {
+				cell.x += step.x;
+				t.x += delta.x;
+			} else {
+				cell.y += step.y;
+				t.y += delta.y;
+			}
+		}
+		rows.sort_unstable();
+		rows.dedup();
+		let mut hits: Vec<(usize, f32)> = rows
+			.into_iter()
+			.filter_map(|row| Some((row, self.bounds[row].ray(origin, dir, max)?)))
+			.collect();
+		hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+		hits
+	}
+
+	fn cell(&self, point: Vec2) -> IVec2 {
+		(point / self.cell_size).floor().as_ivec2()
+	}
+	fn cell_range(&self, bounds: &Aabb2) -> (IVec2, IVec2) {
+		(self.cell(bounds.min), self.cell(bounds.max))
+	}
+	fn cells_of(&self, bounds: &Aabb2) -> impl Iterator<Item = IVec2> + use<> {
+		let (min, max) = self.cell_range(bounds);
+		(min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+	}
+	fn insert(&mut self, row: usize) {
+		for cell in self.cells_of(&self.bounds[row]) {
+			self.cells.entry(cell).or_default().push(row);
+		}
+	}
+	fn remove(&mut self, row: usize) {
+		for cell in self.cells_of(&self.bounds[row]) {
+			let Some(rows) = self.cells.get_mut(&cell) else {
+				continue;
+			};
+			if let Some(i) = rows.iter().position(|&r| r == row) {
+				rows.swap_remove(i);
+			}
+			if rows.is_empty() {
+				self.cells.remove(&cell);
+			}
+		}
+	}
+	fn rename(&mut self, from: usize, to: usize) {
+		for cell in self.cells_of(&self.bounds[from]) {
+			let Some(rows) = self.cells.get_mut(&cell) else {
+				continue;
+			};
+			for row in rows.iter_mut().filter(|r| **r == from) {
+				*row = to;
+			}
+		}
+	}
+	fn candidates(&self, aabb: &Aabb2) -> Vec<usize> {
+		let mut rows = Vec::new();
+		for cell in self.cells_of(aabb) {
+			if let Some(cell_rows) = self.cells.get(&cell) {
+				rows.extend(cell_rows);
+			}
+		}
+		rows.sort_unstable();
+		rows.dedup();
+		rows
+	}
+	/// Where the ray enters and leaves the bounds of every row, so unbounded
+	/// rays stop.
+	fn clip_ray(&self, origin: Vec2, dir: Vec2, max: f32) -> Option<(f32, f32)> {
+		if dir == Vec2::ZERO {
+			return None;
+		}
+		let all = self.bounds.iter().copied().reduce(|a, b| Aabb2 {
+			min: a.min.min(b.min),
+			max: a.max.max(b.max),
+		})?;
+		let (near, far) = all.slabs(origin, dir)?;
+		let (near, far) = (near.max(0.0), far.min(max));
+		(near <= far).then_some((near, far))
+	}
+}
+impl Container for Grid2D {
+	type Ref<'a> = Ref<'a, Self>;
+	type RefMut<'a> = RefMut<'a, Self>;
+
+	fn as_ref<'a>(cont: Ref<'a, Self>) -> Self::Ref<'a> {
+		cont
+	}
+	fn as_mut<'a>(cont: RefMut<'a, Self>) -> Self::RefMut<'a> {
+		cont
+	}
+	fn delete(&mut self, indices: &[usize]) {
+		// Same swap remove as the other containers of the group.
+		for &index in indices {
+			self.remove(index);
+			let last = self.bounds.len() - 1;
+			if index != last {
+				self.rename(last, index);
+			}
+			self.bounds.swap_remove(index);
+		}
+	}
+	fn clear(&mut self, _: usize) {
+		self.bounds.clear();
+		self.cells.clear();
+	}
+	fn permute(&mut self, order: &[usize]) {
+		let mut new_row = vec![0; order.len()];
+		for (row, &from) in order.iter().enumerate() {
+			new_row[from] = row;
+		}
+		for rows in self.cells.values_mut() {
+			for row in rows.iter_mut() {
+				*row = new_row[*row];
+			}
+		}
+		permute_by_swaps(order, |a, b| self.bounds.swap(a, b));
+	}
+	fn row_count(&self) -> Option<usize> {
+		Some(self.bounds.len())
+	}
+	fn memory_usage(&self) -> usize {
+		size_of::<Self>()
+			+ self.bounds.capacity() * size_of::<Aabb2>()
+			+ self
+				.cells
+				.values()
+				.map(|rows| size_of::<(IVec2, Vec<usize>)>() + rows.capacity() * size_of::<usize>())
+				.sum::<usize>()
+	}
+	fn capacity(&self) -> Option<usize> {
+		Some(self.bounds.capacity())
+	}
+	fn reserve(&mut self, additional: usize) {
+		self.bounds.reserve(additional);
+	}
+	fn shrink_to_fit(&mut self) {
+		self.bounds.shrink_to_fit();
+		self.cells.shrink_to_fit();
+	}
+}
+
+component!(pub Spatial2D: Grid2D, new_spatial_2d);
+pub fn new_spatial_2d(
+	ContMut(mut grid): ContMut<Spatial2D>,
+	CompRef(transforms): CompRef<Transforms2D>,
+	_: &mut NewArgs,
+) {
+	// Transforms of the new rows were created first, being a dependency.
+	for transform in &transforms[grid.len()..] {
+		grid.push(Aabb2::of_transform(transform));
+	}
+}
+/// Moves rows of the grid whose transform changed.
+pub fn update_spatial_2d(
+	ContMut(mut grid): ContMut<Spatial2D>,
+	CompRef(transforms): CompRef<Transforms2D>,
+	_: &mut (),
+) {
+	for (row, transform) in transforms.iter().enumerate() {
+		grid.set(row, Aabb2::of_transform(transform));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn square(x: f32, y: f32) -> Aabb2 {
+		Aabb2::from_center(Vec2::new(x, y), Vec2::splat(0.25))
+	}
+
+	/// Rows 0 to 3 around (0.5, 0.5), (2.5, 0.5), (0.5, 2.5) and (5.5, 5.5).
+	fn grid() -> Grid2D {
+		let mut grid = Grid2D::new(1.0);
+		for (x, y) in [(0.5, 0.5), (2.5, 0.5), (0.5, 2.5), (5.5, 5.5)] {
+			grid.push(square(x, y));
+		}
+		grid
+	}
+
+	#[test]
+	fn ray_on_a_face() {
+		let unit = Aabb2::new(Vec2::ZERO, Vec2::ONE);
+		assert_eq!(unit.ray(Vec2::new(-1.0, 0.0), Vec2::X, 10.0), Some(1.0));
+		assert_eq!(unit.ray(Vec2::new(1.0, -2.0), Vec2::Y, 10.0), Some(2.0));
+		assert_eq!(unit.ray(Vec2::new(-1.0, 1.5), Vec2::X, 10.0), None);
+		assert_eq!(unit.ray(Vec2::new(-1.0, 0.0), Vec2::X, 0.5), None);
+	}
+
+	#[test]
+	fn queries() {
+		let grid = grid();
+		assert_eq!(
+			grid.query_aabb(&Aabb2::new(Vec2::ZERO, Vec2::splat(3.0))),
+			[0, 1, 2]
+		);
+		assert_eq!(grid.query_radius(Vec2::new(1.5, 0.5), 0.8), [0, 1]);
+		assert_eq!(
+			grid.query_ray(Vec2::new(0.0, 0.5), Vec2::X, 10.0),
+			[(0, 0.25), (1, 2.25)]
+		);
+		// Along the bottom face of rows 0 and 1.
+		assert_eq!(
+			grid.query_ray(Vec2::new(0.0, 0.25), Vec2::X, 10.0),
+			[(0, 0.25), (1, 2.25)]
+		);
+	}
+
+	#[test]
+	fn queries_after_delete() {
+		let mut grid = grid();
+		// Row 3 is swapped into row 0.
+		grid.delete(&[0]);
+		assert_eq!(grid.len(), 3);
+		assert_eq!(grid.query_aabb(&Aabb2::new(Vec2::ZERO, Vec2::ONE)), []);
+		assert_eq!(
+			grid.query_aabb(&Aabb2::new(Vec2::splat(5.0), Vec2::splat(6.0))),
+			[0]
+		);
+		assert_eq!(grid.query_radius(Vec2::new(0.5, 2.0), 0.5), [2]);
+		assert_eq!(
+			grid.query_ray(Vec2::new(5.5, 0.0), Vec2::Y, 10.0),
+			[(0, 5.25)]
+		);
+		assert_eq!(
+			grid.query_ray(Vec2::new(0.0, 0.5), Vec2::X, 10.0),
+			[(1, 2.25)]
+		);
+	}
+
+	#[test]
+	fn queries_after_permute() {
+		let mut grid = grid();
+		grid.permute(&[3, 2, 0, 1]);
+		assert_eq!(grid.bounds()[0], square(5.5, 5.5));
+		assert_eq!(
+			grid.query_aabb(&Aabb2::new(Vec2::ZERO, Vec2::splat(3.0))),
+			[1, 2, 3]
+		);
+		assert_eq!(grid.query_radius(Vec2::new(0.5, 2.0), 0.5), [1]);
+		assert_eq!(
+			grid.query_ray(Vec2::new(0.0, 0.5), Vec2::X, 10.0),
+			[(2, 0.25), (3, 2.25)]
+		);
+		let diagonal = grid.query_ray(Vec2::ZERO, Vec2::ONE.normalize(), 10.0);
+		assert_eq!(diagonal.iter().map(|hit| hit.0).collect::<Vec<_>>(), [2, 0]);
+		// Rows still move between cells after the permute.
+		grid.set(0, square(0.5, 0.5));
+		assert_eq!(grid.query_aabb(&Aabb2::new(Vec2::ZERO, Vec2::ONE)), [0, 2]);
+		assert_eq!(
+			grid.query_aabb(&Aabb2::new(Vec2::splat(5.0), Vec2::splat(6.0))),
+			[]
+		);
+	}
+}
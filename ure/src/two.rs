@@ -9,7 +9,6 @@ use ure_data::{
 	containers::OneOrMany,
 	glob::{CompMut, CompRef, ContMut, ContRef, Len},
 	group::Data,
-	method::MethodTrait,
 	resource::Resource,
 };
 use wgpu::{
@@ -187,6 +186,7 @@ pub fn new_meshes_2d(ContMut(mut meshes): ContMut<Meshes2D>, args: &mut NewArgs)
 	};
 	if let Some(args) = args.take::<Meshes2D>() {
 		vec.extend(args);
+		return;
 	}
 	let empty = EMPTY.load();
 	for _ in 0..args.len() {
@@ -334,6 +334,11 @@ impl<Key: slotmap::Key> Visuals2D<Key> {
 		group.add_component::<Colors>().unwrap();
 		group.add_component::<Instances2D>().unwrap();
 		group.add_component::<Meshes2D>().unwrap();
+		#[cfg(feature = "reflect")]
+		{
+			group.enable_reflect::<Transforms2D>().unwrap();
+			group.enable_reflect::<Colors>().unwrap();
+		}
 		self.keys.push(key);
 	}
 	pub fn begin_pass<'a>(
@@ -0,0 +1,86 @@
+use std::{
+	sync::{
+		Mutex,
+		atomic::{AtomicBool, Ordering},
+	},
+	thread::sleep,
+	time::{Duration, Instant},
+};
+
+use slotmap::DefaultKey;
+use ure::app::{AppProxy, Game, HeadlessApp, Runner, Time, WindowSystem};
+use ure_data::{
+	cell::RefCell,
+	group::{Data, Group},
+};
+
+const DELTA: Duration = Duration::from_millis(2);
+
+static JUMPED: AtomicBool = AtomicBool::new(false);
+static DELTAS: Mutex<Vec<Duration>> = Mutex::new(Vec::new());
+
+struct Jumper {
+	proxy: AppProxy,
+	data: Data<DefaultKey>,
+	window_system: WindowSystem<DefaultKey>,
+}
+impl Game for Jumper {
+	fn new(proxy: AppProxy) -> Self {
+		let mut data = Data::new();
+		let mut window_system = WindowSystem::new(proxy.clone());
+		let windows = data.insert(RefCell::new(Group::default()));
+		window_system.add(&data, windows);
+		Self {
+			proxy,
+			data,
+			window_system,
+		}
+	}
+	fn run(self) {
+		let input = self.proxy.clone();
+		Runner::new(self.proxy, self.window_system)
+			.fixed_delta(DELTA)
+			.on_update(move |data| {
+				DELTAS
+					.lock()
+					.unwrap()
+					.push(data.resource::<Time>().unwrap().raw_delta());
+				let actions = input.input().get_input();
+				if actions.actions.first().is_some_and(|a| a.get_digital(0)) {
+					JUMPED.store(true, Ordering::Relaxed);
+				}
+				Ok(())
+			})
+			.run(self.data)
+			.unwrap();
+	}
+}
+
+#[test]
+fn runs_until_closed() {
+	let mut app = HeadlessApp::<Jumper>::new();
+	app.start();
+	app.input().inject_digital(0, 0, true);
+
+	let start = Instant::now();
+	while !JUMPED.load(Ordering::Relaxed) {
+		assert!(
+			start.elapsed() < Duration::from_secs(10),
+			"input never seen"
+		);
+		sleep(Duration::from_millis(1));
+	}
+	assert!(!app.has_exited());
+
+	app.close();
+	let start = Instant::now();
+	while !app.has_exited() {
+		assert!(start.elapsed() < Duration::from_secs(10), "never exited");
+		sleep(Duration::from_millis(1));
+	}
+	app.join().unwrap();
+
+	let deltas = DELTAS.lock().unwrap();
+	assert!(!deltas.is_empty());
+	assert!(deltas.iter().all(|&delta| delta == DELTA));
+}
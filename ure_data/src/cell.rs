@@ -0,0 +1,280 @@
+//! Interior mutability used by groups, containers and resources.
+//!
+//! By default these are the [std::cell] types and [Rc](std::rc::Rc). With the
+//! `sync` feature they are backed by [parking_lot] locks and [Arc](std::sync::Arc),
+//! and every container, resource and connected method must be `Send + Sync`,
+//! so a [Data](crate::group::Data) can be shared with worker threads.
+//!
+//! With `sync`, a borrow waits for conflicting borrows on other threads
+//! instead of panicking. Borrowing mutably what the same thread already
+//! borrows deadlocks, use `try_borrow_mut` where that can happen.
+
+use std::any::Any;
+
+#[cfg(not(feature = "sync"))]
+pub use std::{
+	cell::{BorrowError, BorrowMutError, Ref, RefCell, RefMut},
+	rc::Rc as Shared,
+};
+
+#[cfg(feature = "sync")]
+pub use std::sync::Arc as Shared;
+#[cfg(feature = "sync")]
+pub use sync::{BorrowError, BorrowMutError, Ref, RefCell, RefMut};
+
+/// `Send + Sync` with the `sync` feature, implemented by every type without.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSync for T {}
+#[cfg(feature = "sync")]
+pub trait MaybeSync: Send + Sync {}
+#[cfg(feature = "sync")]
+impl<T: ?Sized + Send + Sync> MaybeSync for T {}
+
+#[cfg(not(feature = "sync"))]
+pub type AnyBox = Box<dyn Any>;
+#[cfg(feature = "sync")]
+pub type AnyBox = Box<dyn Any + Send + Sync>;
+
+#[cfg(not(feature = "sync"))]
+pub type AnyShared = Shared<dyn Any>;
+#[cfg(feature = "sync")]
+pub type AnyShared = Shared<dyn Any + Send + Sync>;
+
+#[cfg(feature = "sync")]
+mod sync {
+	use std::{
+		fmt::{Debug, Display},
+		marker::PhantomData,
+		ops::{Deref, DerefMut},
+		ptr::NonNull,
+	};
+
+	use parking_lot::{
+		RawRwLock, RwLock,
+		lock_api::{RawRwLock as _, RawRwLockRecursive as _},
+	};
+
+	/// A [RwLock] with the API of [std::cell::RefCell].
+	#[derive(Default)]
+	pub struct RefCell<T: ?Sized>(RwLock<T>);
+	impl<T> RefCell<T> {
+		pub const fn new(value: T) -> Self {
+			Self(RwLock::new(value))
+		}
+		pub fn into_inner(self) -> T {
+			self.0.into_inner()
+		}
+		pub fn replace(&self, value: T) -> T {
+			std::mem::replace(&mut *self.borrow_mut(), value)
+		}
+		pub fn take(&self) -> T
+		where
+			T: Default,
+		{
+			self.replace(T::default())
+		}
+	}
+	impl<T: ?Sized> RefCell<T> {
+		/// Waits for mutable borrows on other threads to end.
+		pub fn borrow(&self) -> Ref<'_, T> {
+			// SAFETY: Unlocked when the `Ref` drops.
+			unsafe { self.0.raw().lock_shared_recursive() };
+			unsafe { Ref::new(&self.0) }
+		}
+		pub fn try_borrow(&self) -> Result<Ref<'_, T>, BorrowError> {
+			if unsafe { self.0.raw().try_lock_shared_recursive() } {
+				Ok(unsafe { Ref::new(&self.0) })
+			} else {
+				Err(BorrowError)
+			}
+		}
+		/// Waits for every borrow on other threads to end.
+		pub fn borrow_mut(&self) -> RefMut<'_, T> {
+			unsafe { self.0.raw().lock_exclusive() };
+			unsafe { RefMut::new(&self.0) }
+		}
+		pub fn try_borrow_mut(&self) -> Result<RefMut<'_, T>, BorrowMutError> {
+			if unsafe { self.0.raw().try_lock_exclusive() } {
+				Ok(unsafe { RefMut::new(&self.0) })
+			} else {
+				Err(BorrowMutError)
+			}
+		}
+		pub fn get_mut(&mut self) -> &mut T {
+			self.0.get_mut()
+		}
+	}
+	impl<T> From<T> for RefCell<T> {
+		fn from(value: T) -> Self {
+			Self::new(value)
+		}
+	}
+	impl<T: ?Sized + Debug> Debug for RefCell<T> {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			match self.try_borrow() {
+				Ok(value) => f.debug_struct("RefCell").field("value", &&*value).finish(),
+				Err(_) => f.debug_struct("RefCell").finish_non_exhaustive(),
+			}
+		}
+	}
+
+	/// A shared borrow of a [RefCell], mappable like [std::cell::Ref].
+	pub struct Ref<'a, T: ?Sized> {
+		value: NonNull<T>,
+		raw: &'a RawRwLock,
+		_marker: PhantomData<&'a T>,
+	}
+	impl<'a, T: ?Sized> Ref<'a, T> {
+		/// # Safety
+		/// The lock must be held shared, it is released on drop.
+		unsafe fn new(lock: &'a RwLock<T>) -> Self {
+			Self {
+				value: unsafe { NonNull::new_unchecked(lock.data_ptr()) },
+				raw: unsafe { lock.raw() },
+				_marker: PhantomData,
+			}
+		}
+		#[allow(clippy::should_implement_trait)]
+		pub fn clone(orig: &Self) -> Self {
+			orig.raw.lock_shared_recursive();
+			Self {
+				value: orig.value,
+				raw: orig.raw,
+				_marker: PhantomData,
+			}
+		}
+		pub fn map<U: ?Sized>(orig: Self, f: impl FnOnce(&T) -> &U) -> Ref<'a, U> {
+			let value = NonNull::from(f(&orig));
+			let raw = orig.raw;
+			std::mem::forget(orig);
+			Ref {
+				value,
+				raw,
+				_marker: PhantomData,
+			}
+		}
+		pub fn filter_map<U: ?Sized>(
+			orig: Self,
+			f: impl FnOnce(&T) -> Option<&U>,
+		) -> Result<Ref<'a, U>, Self> {
+			match f(&orig).map(NonNull::from) {
+				Some(value) => {
+					let raw = orig.raw;
+					std::mem::forget(orig);
+					Ok(Ref {
+						value,
+						raw,
+						_marker: PhantomData,
+					})
+				}
+				None => Err(orig),
+			}
+		}
+	}
+	impl<T: ?Sized> Deref for Ref<'_, T> {
+		type Target = T;
+
+		fn deref(&self) -> &T {
+			// SAFETY: The lock is held shared for as long as `self`.
+			unsafe { self.value.as_ref() }
+		}
+	}
+	impl<T: ?Sized> Drop for Ref<'_, T> {
+		fn drop(&mut self) {
+			unsafe { self.raw.unlock_shared() };
+		}
+	}
+	impl<T: ?Sized + Debug> Debug for Ref<'_, T> {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			(**self).fmt(f)
+		}
+	}
+
+	/// A mutable borrow of a [RefCell], mappable like [std::cell::RefMut].
+	pub struct RefMut<'a, T: ?Sized> {
+		value: NonNull<T>,
+		raw: &'a RawRwLock,
+		_marker: PhantomData<&'a mut T>,
+	}
+	impl<'a, T: ?Sized> RefMut<'a, T> {
+		/// # Safety
+		/// The lock must be held exclusively, it is released on drop.
+		unsafe fn new(lock: &'a RwLock<T>) -> Self {
+			Self {
+				value: unsafe { NonNull::new_unchecked(lock.data_ptr()) },
+				raw: unsafe { lock.raw() },
+				_marker: PhantomData,
+			}
+		}
+		pub fn map<U: ?Sized>(mut orig: Self, f: impl FnOnce(&mut T) -> &mut U) -> RefMut<'a, U> {
+			let value = NonNull::from(f(&mut orig));
+			let raw = orig.raw;
+			std::mem::forget(orig);
+			RefMut {
+				value,
+				raw,
+				_marker: PhantomData,
+			}
+		}
+		pub fn filter_map<U: ?Sized>(
+			mut orig: Self,
+			f: impl FnOnce(&mut T) -> Option<&mut U>,
+		) -> Result<RefMut<'a, U>, Self> {
+			match f(&mut orig).map(NonNull::from) {
+				Some(value) => {
+					let raw = orig.raw;
+					std::mem::forget(orig);
+					Ok(RefMut {
+						value,
+						raw,
+						_marker: PhantomData,
+					})
+				}
+				None => Err(orig),
+			}
+		}
+	}
+	impl<T: ?Sized> Deref for RefMut<'_, T> {
+		type Target = T;
+
+		fn deref(&self) -> &T {
+			// SAFETY: The lock is held exclusively for as long as `self`.
+			unsafe { self.value.as_ref() }
+		}
+	}
+	impl<T: ?Sized> DerefMut for RefMut<'_, T> {
+		fn deref_mut(&mut self) -> &mut T {
+			unsafe { self.value.as_mut() }
+		}
+	}
+	impl<T: ?Sized> Drop for RefMut<'_, T> {
+		fn drop(&mut self) {
+			unsafe { self.raw.unlock_exclusive() };
+		}
+	}
+	impl<T: ?Sized + Debug> Debug for RefMut<'_, T> {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			(**self).fmt(f)
+		}
+	}
+
+	#[derive(Debug, Clone, Copy)]
+	pub struct BorrowError;
+	impl Display for BorrowError {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			write!(f, "Already mutably borrowed.")
+		}
+	}
+	impl std::error::Error for BorrowError {}
+
+	#[derive(Debug, Clone, Copy)]
+	pub struct BorrowMutError;
+	impl Display for BorrowMutError {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			write!(f, "Already borrowed.")
+		}
+	}
+	impl std::error::Error for BorrowMutError {}
+}
@@ -0,0 +1,386 @@
+use std::{error::Error, fmt::Display};
+
+use slotmap::SecondaryMap;
+
+use crate::{
+	cell::{MaybeSync, RefMut},
+	components::{Component, ComponentDependency, ComponentId, NewArgs},
+	glob::GlobuleRef,
+	group::{Data, Group},
+	resource::MissingResource,
+};
+
+#[cfg(not(feature = "sync"))]
+type Modify = dyn FnOnce(&mut Group) -> Result<(), Box<dyn Error>>;
+#[cfg(feature = "sync")]
+type Modify = dyn FnOnce(&mut Group) -> Result<(), Box<dyn Error>> + Send + Sync;
+#[cfg(not(feature = "sync"))]
+type Build = dyn FnOnce(&Group, &[usize]) -> NewArgs;
+#[cfg(feature = "sync")]
+type Build = dyn FnOnce(&Group, &[usize]) -> NewArgs + Send + Sync;
+#[cfg(not(feature = "sync"))]
+type Custom<Key> = dyn FnOnce(&mut Data<Key>) -> Result<(), Box<dyn Error>>;
+#[cfg(feature = "sync")]
+type Custom<Key> = dyn FnOnce(&mut Data<Key>) -> Result<(), Box<dyn Error>> + Send + Sync;
+
+enum Command<Key: slotmap::Key> {
+	New(Key, NewArgs),
+	Delete(Key, Vec<usize>),
+	Modify(Key, Box<Modify>),
+	Move {
+		from: Key,
+		to: Key,
+		rows: Vec<usize>,
+		build: Box<Build>,
+	},
+	Custom(Box<Custom<Key>>),
+}
+
+/// Structural changes recorded while groups are borrowed, stored as a [Data]
+/// resource and applied by [flush_commands].
+pub struct CommandQueue<Key: slotmap::Key> {
+	commands: Vec<Command<Key>>,
+}
+impl<Key: slotmap::Key> Default for CommandQueue<Key> {
+	fn default() -> Self {
+		Self {
+			commands: Vec::new(),
+		}
+	}
+}
+impl<Key: slotmap::Key> CommandQueue<Key> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	pub fn len(&self) -> usize {
+		self.commands.len()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.commands.is_empty()
+	}
+	/// Creates rows in the group at `key`.
+	pub fn new_rows(&mut self, key: Key, args: NewArgs) {
+		self.commands.push(Command::New(key, args));
+	}
+	/// Deletes rows from the group at `key`. Duplicate rows and rows already
+	/// deleted by an earlier command of the same flush are ignored.
+	pub fn delete(&mut self, key: Key, rows: &[usize]) {
+		self.commands.push(Command::Delete(key, rows.to_vec()));
+	}
+	pub fn add_component<C: Component>(&mut self, key: Key)
+	where
+		C::Container: Default,
+	{
+		self.modify(key, |group| Ok(group.add_component::<C>()?));
+	}
+	/// Adds a component to the group at `key` with a container already
+	/// holding a value for each of its rows.
+	pub fn add_container<C: Component>(&mut self, key: Key, container: C::Container) {
+		self.modify(key, |group| Ok(group.add_container::<C>(container)?));
+	}
+	/// Runs `f` on the group at `key`.
+	pub fn modify(
+		&mut self,
+		key: Key,
+		f: impl FnOnce(&mut Group) -> Result<(), Box<dyn Error>> + MaybeSync + 'static,
+	) {
+		self.commands.push(Command::Modify(key, Box::new(f)));
+	}
+	/// Moves rows from one group to another.
+	///
+	/// `build` reads the rows from the source group and returns the arguments
+	/// to create them with in the target group. The rows are then deleted from
+	/// the source. `build` gets the rows where they are when the command is
+	/// applied, sorted descending.
+	///
+	/// The source rows are kept only if none were created in the target. If
+	/// a `new` method of the target fails, they are still deleted and the
+	/// first error is returned.
+	pub fn move_rows(
+		&mut self,
+		from: Key,
+		to: Key,
+		rows: &[usize],
+		build: impl FnOnce(&Group, &[usize]) -> NewArgs + MaybeSync + 'static,
+	) {
+		self.commands.push(Command::Move {
+			from,
+			to,
+			rows: rows.to_vec(),
+			build: Box::new(build),
+		});
+	}
+	/// Runs `f` on the whole [Data], e.g. to insert or remove groups.
+	pub fn custom(
+		&mut self,
+		f: impl FnOnce(&mut Data<Key>) -> Result<(), Box<dyn Error>> + MaybeSync + 'static,
+	) {
+		self.commands.push(Command::Custom(Box::new(f)));
+	}
+}
+
+/// Extractor recording commands into the [Data]'s [CommandQueue].
+pub struct Commands<'a, Key: slotmap::Key> {
+	queue: RefMut<'a, CommandQueue<Key>>,
+	key: Option<Key>,
+}
+impl<'a, Key: slotmap::Key> Commands<'a, Key> {
+	/// Key of the group the method is running on, if known.
+	pub fn key(&self) -> Option<Key> {
+		self.key
+	}
+	pub fn queue(&mut self) -> &mut CommandQueue<Key> {
+		&mut self.queue
+	}
+	pub fn new_rows(&mut self, key: Key, args: NewArgs) {
+		self.queue.new_rows(key, args);
+	}
+	pub fn delete(&mut self, key: Key, rows: &[usize]) {
+		self.queue.delete(key, rows);
+	}
+	pub fn add_component<C: Component>(&mut self, key: Key)
+	where
+		C::Container: Default,
+	{
+		self.queue.add_component::<C>(key);
+	}
+	pub fn add_container<C: Component>(&mut self, key: Key, container: C::Container) {
+		self.queue.add_container::<C>(key, container);
+	}
+	pub fn move_rows(
+		&mut self,
+		from: Key,
+		to: Key,
+		rows: &[usize],
+		build: impl FnOnce(&Group, &[usize]) -> NewArgs + MaybeSync + 'static,
+	) {
+		self.queue.move_rows(from, to, rows, build);
+	}
+	pub fn custom(
+		&mut self,
+		f: impl FnOnce(&mut Data<Key>) -> Result<(), Box<dyn Error>> + MaybeSync + 'static,
+	) {
+		self.queue.custom(f);
+	}
+}
+impl<Key: slotmap::Key> ComponentDependency for Commands<'_, Key> {
+	fn dependencies() -> Vec<ComponentId> {
+		Vec::new()
+	}
+}
+impl<'a, Key: slotmap::Key + 'static> TryFrom<GlobuleRef<'a, '_>> for Commands<'a, Key> {
+	type Error = MissingResource;
+
+	fn try_from(value: GlobuleRef<'a, '_>) -> Result<Self, Self::Error> {
+		Ok(Self {
+			queue: value
+				.resource_mut::<CommandQueue<Key>>()
+				.ok_or(MissingResource::of::<CommandQueue<Key>>())?,
+			key: value.key(),
+		})
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MissingGroup;
+impl Display for MissingGroup {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Command targets a group not in the data.")
+	}
+}
+impl Error for MissingGroup {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RowOutOfBounds {
+	pub row: usize,
+	pub len: usize,
+}
+impl Display for RowOutOfBounds {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"Row {} is out of bounds for a group of {}.",
+			self.row, self.len
+		)
+	}
+}
+impl Error for RowOutOfBounds {}
+
+/// Where the rows a group had when the flush started are now, as commands
+/// swap remove rows.
+struct RowMap {
+	/// Current row of each original row, [None] once deleted.
+	current: Vec<Option<usize>>,
+	/// Original row of each current row, [None] for rows created since.
+	original: Vec<Option<usize>>,
+}
+impl RowMap {
+	fn new(len: usize) -> Self {
+		Self {
+			current: (0..len).map(Some).collect(),
+			original: (0..len).map(Some).collect(),
+		}
+	}
+	/// Follows rows created or deleted other than by [RowMap::delete], e.g.
+	/// by [CommandQueue::modify]. Deleted rows cannot be told apart, so the
+	/// group's rows are taken as original again.
+	fn sync(&mut self, len: usize) {
+		if len < self.original.len() {
+			*self = Self::new(len);
+		} else {
+			self.original.resize(len, None);
+		}
+	}
+	/// Current rows of original `rows`, sorted descending. Rows already
+	/// deleted are left out.
+	fn map(&self, rows: &[usize]) -> Result<Vec<usize>, RowOutOfBounds> {
+		let mut mapped = Vec::with_capacity(rows.len());
+		for &row in rows {
+			match self.current.get(row) {
+				Some(Some(current)) => mapped.push(*current),
+				Some(None) => {}
+				None => {
+					return Err(RowOutOfBounds {
+						row,
+						len: self.current.len(),
+					});
+				}
+			}
+		}
+		// Descending, so swap removes never move a row still to be deleted.
+		mapped.sort_unstable_by(|a, b| b.cmp(a));
+		mapped.dedup();
+		Ok(mapped)
+	}
+	/// Swap removes current `rows`, sorted descending.
+	fn delete(&mut self, rows: &[usize]) {
+		for &row in rows {
+			if let Some(original) = self.original.swap_remove(row) {
+				self.current[original] = None;
+			}
+			if let Some(&Some(moved)) = self.original.get(row) {
+				self.current[moved] = Some(row);
+			}
+		}
+	}
+}
+
+fn row_map<'m, Key: slotmap::Key>(
+	maps: &'m mut SecondaryMap<Key, RowMap>,
+	key: Key,
+	group: &Group,
+) -> &'m mut RowMap {
+	if !maps.contains_key(key) {
+		maps.insert(key, RowMap::new(group.len()));
+	}
+	let map = &mut maps[key];
+	map.sync(group.len());
+	map
+}
+
+/// A command that failed during [flush_commands].
+#[derive(Debug)]
+pub struct CommandError {
+	/// Position of the command in the queue.
+	pub index: usize,
+	pub error: Box<dyn Error>,
+}
+impl Display for CommandError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Command {} failed: {}", self.index, self.error)
+	}
+}
+impl Error for CommandError {}
+
+/// Applies every queued command in the order it was recorded.
+///
+/// Rows given to commands are those of the group when the flush started.
+/// Commands applied before a delete or move may have swapped rows around, so
+/// its rows are followed to where they are now.
+///
+/// A failed command does not stop the rest. Commands queued while flushing
+/// are left for the next flush.
+pub fn flush_commands<Key: slotmap::Key + 'static>(
+	data: &mut Data<Key>,
+) -> Result<(), Vec<CommandError>> {
+	let commands = match data.resource_mut::<CommandQueue<Key>>() {
+		Some(mut queue) => std::mem::take(&mut queue.commands),
+		None => return Ok(()),
+	};
+	let mut maps = SecondaryMap::new();
+	let errors: Vec<CommandError> = commands
+		.into_iter()
+		.enumerate()
+		.filter_map(|(index, command)| {
+			apply(data, &mut maps, command)
+				.err()
+				.map(|error| CommandError { index, error })
+		})
+		.collect();
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		Err(errors)
+	}
+}
+
+fn apply<Key: slotmap::Key>(
+	data: &mut Data<Key>,
+	maps: &mut SecondaryMap<Key, RowMap>,
+	command: Command<Key>,
+) -> Result<(), Box<dyn Error>> {
+	match command {
+		Command::New(key, args) => {
+			let mut globule = data.globule_mut(key).ok_or(MissingGroup)?;
+			// The new rows were not there when the flush started.
+			row_map(maps, key, globule.group());
+			globule.new_from_args(args)?;
+		}
+		Command::Delete(key, rows) => {
+			let mut globule = data.globule_mut(key).ok_or(MissingGroup)?;
+			let map = row_map(maps, key, globule.group());
+			let rows = map.map(&rows)?;
+			let deleted = globule.delete(&rows);
+			map.delete(&rows);
+			deleted?;
+		}
+		Command::Modify(key, f) => {
+			let mut group = data.get(key).ok_or(MissingGroup)?.borrow_mut();
+			row_map(maps, key, &group);
+			f(&mut group)?;
+		}
+		Command::Move {
+			from,
+			to,
+			rows,
+			build,
+		} => {
+			let (rows, args) = {
+				let source = data.get(from).ok_or(MissingGroup)?.borrow();
+				let rows = row_map(maps, from, &source).map(&rows)?;
+				let args = build(&source, &rows);
+				(rows, args)
+			};
+			if !data.contains_key(to) {
+				return Err(MissingGroup.into());
+			}
+			let mut target = data.globule_mut(to).unwrap();
+			row_map(maps, to, target.group());
+			let len = target.group().len();
+			let created = match target.new_from_args(args) {
+				// Nothing was moved, the rows stay in the source.
+				Err(error) if target.group().len() == len => return Err(error.into()),
+				created => created,
+			};
+			drop(target);
+			let mut source = data.globule_mut(from).unwrap();
+			let map = row_map(maps, from, source.group());
+			let deleted = source.delete(&rows);
+			map.delete(&rows);
+			created?;
+			deleted?;
+		}
+		Command::Custom(f) => f(data)?,
+	}
+	Ok(())
+}
@@ -1,20 +1,29 @@
 use std::{
 	any::Any,
-	cell::{self, RefCell},
-	collections::HashMap,
+	collections::{HashMap, VecDeque},
 	error::Error,
 	fmt::Display,
 };
 
+use bitvec::vec::BitVec;
+use indexmap::IndexSet;
 use nohash_hasher::BuildNoHashHasher;
 
+#[cfg(feature = "reflect")]
+use crate::reflect::{ReflectError, ReflectVTable, Value};
 use crate::{
-	containers::{Container, NewDefault},
+	cell::{self, AnyBox, MaybeSync, RefCell, Shared},
+	containers::{Container, NewDefault, Snapshot},
 	glob::{ContMut, GlobuleRef},
 	group::Group,
+	method::{MethodTrait, TryFromGlob},
+	signals::SignalError,
+	snapshot::{ContainerSnapshot, ContainerSnapshots, SnapshotError},
 	util::all_the_tuples,
 };
 
+pub use ure_data_derive::Component;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ComponentId(u64);
 impl nohash_hasher::IsEnabled for ComponentId {}
@@ -37,117 +46,536 @@ pub trait Component: Sized + ComponentDependency + 'static {
 	const ID: ComponentId;
 	type Container: Container;
 
-	type NewArg: Sized + 'static;
+	type NewArg: ArgRows;
 	fn new(glob: GlobuleRef<'_, '_>, args: &mut NewArgs) -> Result<(), Box<dyn Error>>;
-	fn delete(glob: GlobuleRef<'_, '_>, indices: &mut &[usize]) -> Result<(), Box<dyn Error>>;
+	/// Runs before `indices` are removed from every container of the group,
+	/// so other components of the deleted rows are still readable.
+	fn delete(glob: GlobuleRef<'_, '_>, indices: &mut &[usize]) -> Result<(), Box<dyn Error>> {
+		_ = (glob, indices);
+		Ok(())
+	}
+	/// Runs before [Group::clear] removes every row, `indices` holds all of
+	/// them. Runs [Component::delete] by default.
+	fn clear(glob: GlobuleRef<'_, '_>, indices: &mut &[usize]) -> Result<(), Box<dyn Error>> {
+		Self::delete(glob, indices)
+	}
 }
 
+/// Arguments for creating rows, one per component.
+///
+/// Arguments holding one value per row are checked against [NewArgs::len]
+/// before any row is created. Arguments left after every `new` method ran are
+/// logged as unused.
 pub struct NewArgs {
 	len: usize,
-	args: HashMap<ComponentId, Box<dyn Any>, BuildNoHashHasher<ComponentId>>,
+	args: HashMap<ComponentId, Arg, BuildNoHashHasher<ComponentId>>,
+	errors: Vec<ArgError>,
+}
+struct Arg {
+	value: AnyBox,
+	component: &'static str,
+}
+/// The [NewArgsError]s found before any row is created. Kept apart so
+/// [NewArgs] does not hold a [SignalError], which is not `Send`.
+enum ArgError {
+	Length {
+		component: &'static str,
+		expected: usize,
+		found: usize,
+	},
+	Type {
+		component: &'static str,
+	},
 }
 impl NewArgs {
 	pub fn new(num: usize) -> Self {
 		Self {
 			len: num,
 			args: Default::default(),
+			errors: Vec::new(),
 		}
 	}
 	pub const fn len(&self) -> usize {
 		self.len
 	}
+	pub const fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+	/// Takes the argument for `C`. An argument of the wrong type is dropped
+	/// and reported by [Group::new_from_args](crate::group::Group::new_from_args).
 	pub fn take<C: Component>(&mut self) -> Option<C::NewArg> {
-		Some(*self.args.remove(&C::ID)?.downcast().unwrap())
+		let arg = self.args.remove(&C::ID)?;
+		match arg.value.downcast() {
+			Ok(value) => Some(*value),
+			Err(_) => {
+				self.errors.push(ArgError::Type {
+					component: arg.component,
+				});
+				None
+			}
+		}
 	}
 	pub fn with<C: Component>(&mut self, arg: C::NewArg) {
-		self.args.insert(C::ID, Box::new(arg));
+		let component = std::any::type_name::<C>();
+		if let Some(found) = arg.rows()
+			&& found != self.len
+		{
+			self.errors.push(ArgError::Length {
+				component,
+				expected: self.len,
+				found,
+			});
+			return;
+		}
+		self.args.insert(
+			C::ID,
+			Arg {
+				value: Box::new(arg),
+				component,
+			},
+		);
+	}
+	/// Builds the argument for `C` from one value per row.
+	pub fn with_fn<C: Component, T>(&mut self, f: impl FnMut(usize) -> T)
+	where
+		C::NewArg: FromIterator<T>,
+	{
+		self.with::<C>((0..self.len).map(f).collect());
+	}
+	pub(crate) fn check(&mut self) -> Result<(), NewArgsError> {
+		match self.errors.drain(..).next() {
+			Some(ArgError::Length {
+				component,
+				expected,
+				found,
+			}) => Err(NewArgsError::Length {
+				component,
+				expected,
+				found,
+			}),
+			Some(ArgError::Type { component }) => Err(NewArgsError::Type { component }),
+			None => Ok(()),
+		}
+	}
+	pub(crate) fn warn_unused(&self) {
+		for arg in self.args.values() {
+			log::warn!(
+				"Argument for {} was not used by any component.",
+				arg.component
+			);
+		}
 	}
 }
 
-#[macro_export]
-macro_rules! component {
-	($v:vis $name:ident: $container:ty) => {
-$v struct $name;
-impl $crate::components::ComponentDependency for $name {
-	fn dependencies() -> Vec<$crate::components::ComponentId> {
-		Vec::new()
+#[derive(Debug)]
+pub enum NewArgsError {
+	/// The argument does not hold one value per row.
+	Length {
+		component: &'static str,
+		expected: usize,
+		found: usize,
+	},
+	/// The argument is not the component's `NewArg` type.
+	Type { component: &'static str },
+	/// Methods connected to [NEW](crate::group::NEW) failed. The rows were
+	/// still created.
+	Signal(SignalError),
+}
+impl Display for NewArgsError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Length {
+				component,
+				expected,
+				found,
+			} => write!(
+				f,
+				"Argument for {component} has {found} rows, expected {expected}."
+			),
+			Self::Type { component } => write!(f, "Argument for {component} has the wrong type."),
+			Self::Signal(error) => error.fmt(f),
+		}
 	}
 }
-impl $crate::components::Component for $name {
-	const ID: $crate::components::ComponentId = $crate::components::ComponentId::new(std::module_path!(), stringify!($name));
-	type Container = $container;
-
-	type NewArg = ();
+impl Error for NewArgsError {}
 
-	fn new(glob: $crate::glob::GlobuleRef<'_, '_>, args: &mut $crate::components::NewArgs) -> Result<(), Box<dyn std::error::Error>> {
-		$crate::components::new_default::<Self>.call_method(glob, args)
+/// The `NewArg` of a [Component].
+///
+/// Arguments holding one value per row return their count from `rows`, so it
+/// can be checked against the number of rows created.
+pub trait ArgRows: Sized + MaybeSync + 'static {
+	fn rows(&self) -> Option<usize> {
+		None
 	}
-	fn delete(glob: $crate::glob::GlobuleRef<'_, '_>, indices: &mut &[usize]) -> Result<(), Box<dyn std::error::Error>> {
-		$crate::components::delete_default::<Self>.call_method(glob, indices)
+}
+impl ArgRows for () {}
+impl<T: MaybeSync + 'static> ArgRows for Vec<T> {
+	fn rows(&self) -> Option<usize> {
+		Some(self.len())
+	}
+}
+impl<T: MaybeSync + 'static> ArgRows for VecDeque<T> {
+	fn rows(&self) -> Option<usize> {
+		Some(self.len())
 	}
 }
+impl<T: MaybeSync + 'static> ArgRows for Box<[T]> {
+	fn rows(&self) -> Option<usize> {
+		Some(self.len())
+	}
+}
+impl<T: MaybeSync + 'static, S: MaybeSync + 'static> ArgRows for IndexSet<T, S> {
+	fn rows(&self) -> Option<usize> {
+		Some(self.len())
+	}
+}
+impl ArgRows for BitVec {
+	fn rows(&self) -> Option<usize> {
+		Some(self.len())
+	}
+}
+impl<T: ArgRows> ArgRows for Option<T> {
+	fn rows(&self) -> Option<usize> {
+		self.as_ref()?.rows()
+	}
+}
+
+/// Declares a component.
+///
+/// ```ignore
+/// component!(pub Colors: Vec<Srgba>);
+/// component!(pub Colors: Vec<Srgba>, new_colors, Vec<Srgba>);
+/// component!(pub Windows: Vec<Arc<Window>>, new_windows, Vec<WindowAttributes>; delete_windows);
+/// ```
+///
+/// The optional method after `;` runs before rows are removed, see [Component::delete].
+#[macro_export]
+macro_rules! component {
+	($v:vis $name:ident: $container:ty $(; $delete:expr)?) => {
+$crate::component!(@impl $v $name: $container, (), $crate::components::new_default::<$name> $(, $delete)?);
+	};
+	($v:vis $name:ident: $container:ty, $new:expr, $new_arg:ty $(; $delete:expr)?) => {
+$crate::component!(@impl $v $name: $container, $new_arg, $new $(, $delete)?);
 	};
-	($v:vis $name:ident: $container:ty, $new:expr $(, $new_arg:ty)?) => {
+	($v:vis $name:ident: $container:ty, $new:expr $(; $delete:expr)?) => {
+$crate::component!(@impl $v $name: $container, (), $new $(, $delete)?);
+	};
+	(@impl $v:vis $name:ident: $container:ty, $new_arg:ty, $new:expr $(, $delete:expr)?) => {
 $v struct $name;
 impl $crate::components::ComponentDependency for $name {
+	#[allow(unused_mut)]
 	fn dependencies() -> Vec<$crate::components::ComponentId> {
-		<&dyn $crate::method::MethodTrait<_, _, _>>::method_dependencies(&(&$new as &dyn $crate::method::MethodTrait<_, _, _>))
+		let mut dependencies = $crate::components::NewMethod::dependencies(&$new);
+		$(dependencies.extend($crate::components::DeleteMethod::dependencies(&$delete));)?
+		dependencies
 	}
 }
 impl $crate::components::Component for $name {
 	const ID: $crate::components::ComponentId = $crate::components::ComponentId::new(std::module_path!(), stringify!($name));
 	type Container = $container;
 
-	type NewArg = ($($new_arg)?);
+	type NewArg = $new_arg;
 	fn new(glob: $crate::glob::GlobuleRef<'_, '_>, args: &mut $crate::components::NewArgs) -> Result<(), Box<dyn std::error::Error>> {
-		($new).call_method(glob, args)
+		$crate::components::NewMethod::call_new($new, glob, args)
 	}
+	$(
 	fn delete(glob: $crate::glob::GlobuleRef<'_, '_>, indices: &mut &[usize]) -> Result<(), Box<dyn std::error::Error>> {
-		$crate::components::delete_default::<Self>.call_method(glob, indices)
+		$crate::components::DeleteMethod::call_delete($delete, glob, indices)
 	}
+	)?
 }
 	};
 }
 
+#[diagnostic::on_unimplemented(
+	message = "`{Self}` cannot be used as the `new` method of a component",
+	label = "invalid `new` method",
+	note = "`new` methods take extractors such as `ContMut<C>` or `CompRef<C>`, then `&mut NewArgs`, and return `()`"
+)]
+pub trait NewMethod<T> {
+	fn dependencies(&self) -> Vec<ComponentId>;
+	fn call_new<'a, 'b>(
+		self,
+		glob: GlobuleRef<'a, 'b>,
+		args: &mut NewArgs,
+	) -> Result<(), Box<dyn Error>>
+	where
+		T: TryFromGlob<'a, 'b>;
+}
+impl<T: ComponentDependency, F> NewMethod<T> for F
+where
+	F: for<'c> MethodTrait<T, &'c mut NewArgs, ()>,
+{
+	fn dependencies(&self) -> Vec<ComponentId> {
+		T::dependencies()
+	}
+	fn call_new<'a, 'b>(
+		self,
+		glob: GlobuleRef<'a, 'b>,
+		args: &mut NewArgs,
+	) -> Result<(), Box<dyn Error>>
+	where
+		T: TryFromGlob<'a, 'b>,
+	{
+		self.call_method(glob, args)
+	}
+}
+
+#[diagnostic::on_unimplemented(
+	message = "`{Self}` cannot be used as the `delete` method of a component",
+	label = "invalid `delete` method",
+	note = "`delete` methods take extractors such as `ContMut<C>` or `CompRef<C>`, then `&mut &[usize]`, and return `()`"
+)]
+pub trait DeleteMethod<T> {
+	fn dependencies(&self) -> Vec<ComponentId>;
+	fn call_delete<'a, 'b>(
+		self,
+		glob: GlobuleRef<'a, 'b>,
+		indices: &mut &[usize],
+	) -> Result<(), Box<dyn Error>>
+	where
+		T: TryFromGlob<'a, 'b>;
+}
+impl<T: ComponentDependency, F> DeleteMethod<T> for F
+where
+	F: for<'c, 'd> MethodTrait<T, &'c mut &'d [usize], ()>,
+{
+	fn dependencies(&self) -> Vec<ComponentId> {
+		T::dependencies()
+	}
+	fn call_delete<'a, 'b>(
+		self,
+		glob: GlobuleRef<'a, 'b>,
+		indices: &mut &[usize],
+	) -> Result<(), Box<dyn Error>>
+	where
+		T: TryFromGlob<'a, 'b>,
+	{
+		self.call_method(glob, indices)
+	}
+}
+
 pub fn new_default<C: Component>(ContMut(mut c): ContMut<C>, args: &mut NewArgs)
 where
-	for<'a> C: ComponentGroup<ContainersRefMut<'a> = std::cell::RefMut<'a, C::Container>>,
+	for<'a> C: ComponentGroup<ContainersRefMut<'a> = cell::RefMut<'a, C::Container>>,
 	C::Container: NewDefault,
 {
 	c.new_default(args.len());
 }
 
-pub fn delete_default<C: ComponentGroup + Component>(
-	ContMut(mut c): ContMut<C>,
-	indices: &mut &[usize],
-) where
-	for<'a> C: ComponentGroup<ContainersRefMut<'a> = std::cell::RefMut<'a, C::Container>>,
-{
-	c.delete(indices);
+/// Object-safe [Container] operations, applied to every container of a group.
+trait AnyContainer: Any + MaybeSync {
+	fn delete(&mut self, indices: &[usize]);
+	fn clear(&mut self, len: usize);
+	fn permute(&mut self, order: &[usize]);
+	fn row_count(&self) -> Option<usize>;
+	fn memory_usage(&self) -> usize;
+	fn capacity(&self) -> Option<usize>;
+	fn reserve(&mut self, additional: usize);
+	fn shrink_to_fit(&mut self);
+}
+impl<C: Container> AnyContainer for C {
+	fn delete(&mut self, indices: &[usize]) {
+		Container::delete(self, indices);
+	}
+	fn clear(&mut self, len: usize) {
+		Container::clear(self, len);
+	}
+	fn permute(&mut self, order: &[usize]) {
+		Container::permute(self, order);
+	}
+	fn row_count(&self) -> Option<usize> {
+		Container::row_count(self)
+	}
+	fn memory_usage(&self) -> usize {
+		Container::memory_usage(self)
+	}
+	fn capacity(&self) -> Option<usize> {
+		Container::capacity(self)
+	}
+	fn reserve(&mut self, additional: usize) {
+		Container::reserve(self, additional);
+	}
+	fn shrink_to_fit(&mut self) {
+		Container::shrink_to_fit(self);
+	}
+}
+impl std::fmt::Debug for dyn AnyContainer {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("AnyContainer").finish_non_exhaustive()
+	}
+}
+
+struct SnapshotFns {
+	snapshot: fn(&dyn AnyContainer) -> ContainerSnapshot,
+	restore: fn(&mut dyn AnyContainer, &ContainerSnapshot),
+}
+fn snapshot_container<S: Snapshot>(container: &dyn AnyContainer) -> ContainerSnapshot {
+	let container = (container as &dyn Any).downcast_ref::<S>().unwrap();
+	ContainerSnapshot {
+		value: Shared::new(container.snapshot()),
+		size: container.snapshot_size(),
+	}
+}
+fn restore_container<S: Snapshot>(container: &mut dyn AnyContainer, snapshot: &ContainerSnapshot) {
+	let container = (container as &mut dyn Any).downcast_mut::<S>().unwrap();
+	container.restore(snapshot.value.downcast_ref::<S>().unwrap());
+}
+
+struct Entry {
+	name: &'static str,
+	container_name: &'static str,
+	container: RefCell<Box<dyn AnyContainer>>,
+	snapshot: Option<SnapshotFns>,
+	/// Last snapshot taken or restored, cleared by any mutable borrow.
+	last: RefCell<Option<ContainerSnapshot>>,
+	#[cfg(feature = "reflect")]
+	reflect: Option<ReflectVTable>,
+}
+impl Entry {
+	fn borrow_mut(&self) -> cell::RefMut<'_, Box<dyn AnyContainer>> {
+		self.last.take();
+		self.container.borrow_mut()
+	}
+}
+impl std::fmt::Debug for Entry {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Entry")
+			.field("name", &self.name)
+			.field("container", &self.container)
+			.field("snapshot", &self.snapshot.is_some())
+			.finish_non_exhaustive()
+	}
 }
 
 #[derive(Debug, Default)]
 pub struct Components {
-	inner: HashMap<ComponentId, RefCell<Box<dyn Any>>, BuildNoHashHasher<ComponentId>>,
+	inner: HashMap<ComponentId, Entry, BuildNoHashHasher<ComponentId>>,
 }
 impl Components {
 	pub fn add<C: Component>(&mut self, container: C::Container) {
-		self.inner.insert(C::ID, RefCell::new(Box::new(container)));
+		self.inner.insert(
+			C::ID,
+			Entry {
+				name: std::any::type_name::<C>(),
+				container_name: std::any::type_name::<C::Container>(),
+				container: RefCell::new(Box::new(container)),
+				snapshot: None,
+				last: RefCell::new(None),
+				#[cfg(feature = "reflect")]
+				reflect: None,
+			},
+		);
+	}
+	/// Lets the container of `C` be captured by [Components::snapshot].
+	pub fn enable_snapshot<C: Component>(&mut self) -> Result<(), MissingDependency>
+	where
+		C::Container: Snapshot,
+	{
+		let entry = self.inner.get_mut(&C::ID).ok_or(MissingDependency(C::ID))?;
+		entry.snapshot = Some(SnapshotFns {
+			snapshot: snapshot_container::<C::Container>,
+			restore: restore_container::<C::Container>,
+		});
+		Ok(())
+	}
+	/// Removes `indices` from every container, see [Container::delete].
+	pub fn delete(&mut self, indices: &[usize]) {
+		for entry in self.inner.values_mut() {
+			entry.last.take();
+			entry.container.get_mut().delete(indices);
+		}
+	}
+	/// Empties every container of `len` rows, see [Container::clear].
+	pub fn clear(&mut self, len: usize) {
+		for entry in self.inner.values_mut() {
+			entry.last.take();
+			entry.container.get_mut().clear(len);
+		}
+	}
+	/// Reorders every container, see [Container::permute].
+	pub fn permute(&mut self, order: &[usize]) {
+		for entry in self.inner.values_mut() {
+			entry.last.take();
+			entry.container.get_mut().permute(order);
+		}
+	}
+	/// See [Container::reserve].
+	pub fn reserve(&mut self, additional: usize) {
+		for entry in self.inner.values_mut() {
+			entry.container.get_mut().reserve(additional);
+		}
+	}
+	/// See [Container::shrink_to_fit].
+	pub fn shrink_to_fit(&mut self) {
+		for entry in self.inner.values_mut() {
+			entry.container.get_mut().shrink_to_fit();
+		}
+	}
+	/// Captures every container, sharing the last snapshot of containers that
+	/// have not been borrowed mutably since.
+	///
+	/// Returns the snapshots and the bytes newly copied.
+	pub(crate) fn snapshot(&self) -> Result<(ContainerSnapshots, usize), SnapshotError> {
+		let mut size = 0;
+		let mut snapshots = HashMap::default();
+		for (id, entry) in self.inner.iter() {
+			let fns = entry
+				.snapshot
+				.as_ref()
+				.ok_or(SnapshotError::NotSnapshot(*id))?;
+			let snapshot = match entry.last.take() {
+				Some(last) => last,
+				None => {
+					let snapshot = (fns.snapshot)(&**entry.container.borrow());
+					size += snapshot.size;
+					snapshot
+				}
+			};
+			*entry.last.borrow_mut() = Some(snapshot.clone());
+			snapshots.insert(*id, snapshot);
+		}
+		Ok((snapshots, size))
+	}
+	/// Whether the components are exactly the snapshot's.
+	pub(crate) fn matches(&self, snapshots: &ContainerSnapshots) -> bool {
+		snapshots.len() == self.inner.len()
+			&& self.inner.keys().all(|id| snapshots.contains_key(id))
 	}
-	pub fn borrow_container<C: Component>(&'_ self) -> Option<std::cell::Ref<'_, C::Container>> {
+	/// Fails without changing anything if the components differ from the
+	/// snapshot's.
+	pub(crate) fn restore(&mut self, snapshots: &ContainerSnapshots) -> Result<(), SnapshotError> {
+		if !self.matches(snapshots) {
+			return Err(SnapshotError::ComponentsChanged);
+		}
+		for (id, entry) in self.inner.iter_mut() {
+			let snapshot = &snapshots[id];
+			if let Some(last) = entry.last.get_mut()
+				&& Shared::ptr_eq(&last.value, &snapshot.value)
+			{
+				continue;
+			}
+			let fns = entry
+				.snapshot
+				.as_ref()
+				.ok_or(SnapshotError::NotSnapshot(*id))?;
+			(fns.restore)(&mut **entry.container.get_mut(), snapshot);
+			*entry.last.borrow_mut() = Some(snapshot.clone());
+		}
+		Ok(())
+	}
+	pub fn borrow_container<C: Component>(&'_ self) -> Option<cell::Ref<'_, C::Container>> {
 		Some(
-			cell::Ref::filter_map(self.inner.get(&C::ID)?.borrow(), |c| {
-				Some(c.downcast_ref::<C::Container>()?)
+			cell::Ref::filter_map(self.inner.get(&C::ID)?.container.borrow(), |c| {
+				Some((&**c as &dyn Any).downcast_ref::<C::Container>()?)
 			})
 			.unwrap(),
 		)
 	}
-	pub fn borrow_container_mut<C: Component>(
-		&'_ self,
-	) -> Option<std::cell::RefMut<'_, C::Container>> {
+	pub fn borrow_container_mut<C: Component>(&'_ self) -> Option<cell::RefMut<'_, C::Container>> {
 		Some(
 			cell::RefMut::filter_map(self.inner.get(&C::ID)?.borrow_mut(), |c| {
-				Some(c.downcast_mut::<C::Container>()?)
+				Some((&mut **c as &mut dyn Any).downcast_mut::<C::Container>()?)
 			})
 			.unwrap(),
 		)
@@ -156,8 +584,8 @@ impl Components {
 		&'_ self,
 	) -> Option<<C::Container as Container>::Ref<'_>> {
 		Some(<C::Container as Container>::as_ref(
-			cell::Ref::filter_map(self.inner.get(&C::ID)?.borrow(), |c| {
-				Some(c.downcast_ref::<C::Container>()?)
+			cell::Ref::filter_map(self.inner.get(&C::ID)?.container.borrow(), |c| {
+				Some((&**c as &dyn Any).downcast_ref::<C::Container>()?)
 			})
 			.unwrap(),
 		))
@@ -167,7 +595,7 @@ impl Components {
 	) -> Option<<C::Container as Container>::RefMut<'_>> {
 		Some(<C::Container as Container>::as_mut(
 			cell::RefMut::filter_map(self.inner.get(&C::ID)?.borrow_mut(), |c| {
-				Some(c.downcast_mut::<C::Container>()?)
+				Some((&mut **c as &mut dyn Any).downcast_mut::<C::Container>()?)
 			})
 			.unwrap(),
 		))
@@ -175,6 +603,102 @@ impl Components {
 	pub fn contains(&self, id: &ComponentId) -> bool {
 		self.inner.contains_key(id)
 	}
+	pub fn ids(&self) -> impl Iterator<Item = ComponentId> {
+		self.inner.keys().copied()
+	}
+	pub fn info(&self, id: ComponentId) -> Option<ComponentInfo> {
+		let entry = self.inner.get(&id)?;
+		let container = entry.container.try_borrow().ok();
+		Some(ComponentInfo {
+			id,
+			name: entry.name,
+			container: entry.container_name,
+			rows: container.as_ref().and_then(|c| c.row_count()),
+			memory: container.as_ref().map(|c| c.memory_usage()),
+			capacity: container.as_ref().and_then(|c| c.capacity()),
+			snapshot: entry.snapshot.is_some(),
+		})
+	}
+}
+
+#[cfg(feature = "reflect")]
+impl Components {
+	pub fn set_reflect(
+		&mut self,
+		id: ComponentId,
+		vtable: ReflectVTable,
+	) -> Result<(), MissingDependency> {
+		self.inner
+			.get_mut(&id)
+			.ok_or(MissingDependency(id))?
+			.reflect = Some(vtable);
+		Ok(())
+	}
+	pub fn reflect(&self, id: ComponentId) -> Option<ReflectVTable> {
+		self.inner.get(&id)?.reflect
+	}
+	/// Reflected components with their type names.
+	pub fn reflected(&self) -> impl Iterator<Item = (ComponentId, &'static str)> {
+		self.inner
+			.iter()
+			.filter(|(_, entry)| entry.reflect.is_some())
+			.map(|(id, entry)| (*id, entry.name))
+	}
+	pub fn reflect_get(&self, id: ComponentId, row: usize) -> Result<Value, ReflectError> {
+		let entry = self.inner.get(&id).ok_or(ReflectError::NotReflect(id))?;
+		let vtable = entry.reflect.ok_or(ReflectError::NotReflect(id))?;
+		let container = entry.container.borrow();
+		(vtable.get)(&**container as &dyn Any, row).ok_or(ReflectError::MissingRow(row))
+	}
+	pub fn reflect_set(
+		&self,
+		id: ComponentId,
+		row: usize,
+		value: &Value,
+	) -> Result<(), ReflectError> {
+		let entry = self.inner.get(&id).ok_or(ReflectError::NotReflect(id))?;
+		let vtable = entry.reflect.ok_or(ReflectError::NotReflect(id))?;
+		let mut container = entry.borrow_mut();
+		(vtable.set)(&mut **container as &mut dyn Any, row, value)
+	}
+}
+
+/// Metadata of a component in a [Group].
+///
+/// `rows`, `memory` and `capacity` are [None] while the container is borrowed mutably.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentInfo {
+	pub id: ComponentId,
+	pub name: &'static str,
+	pub container: &'static str,
+	/// Rows as seen by the container, see [Container::row_count].
+	pub rows: Option<usize>,
+	/// Estimated bytes, see [Container::memory_usage].
+	pub memory: Option<usize>,
+	/// See [Container::capacity].
+	pub capacity: Option<usize>,
+	pub snapshot: bool,
+}
+impl ComponentInfo {
+	/// Whether the container holds a different number of rows than `len`.
+	pub fn diverges(&self, len: usize) -> bool {
+		self.rows.is_some_and(|rows| rows != len)
+	}
+}
+impl Display for ComponentInfo {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} ({})", self.name, self.container)?;
+		if let Some(rows) = self.rows {
+			write!(f, " {rows} rows")?;
+		}
+		if let Some(capacity) = self.capacity {
+			write!(f, " (capacity {capacity})")?;
+		}
+		if let Some(memory) = self.memory {
+			write!(f, " {memory} B")?;
+		}
+		Ok(())
+	}
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -212,13 +736,13 @@ impl<$($C: Component),*> ComponentGroup for ($($C),*) {
 	];
 
 	type ContainersRef<'a> = (
-		$(std::cell::Ref<'a, <$C as Component>::Container>),*
+		$(cell::Ref<'a, <$C as Component>::Container>),*
 	);
 	fn borrow_containers(group: &Group) -> Result<Self::ContainersRef<'_>, MissingDependency> {
 		Ok(($( group.borrow_container::<$C>().ok_or(MissingDependency(<$C as Component>::ID))? ),*))
 	}
 	type ContainersRefMut<'a> = (
-		$(std::cell::RefMut<'a, <$C as Component>::Container>),*
+		$(cell::RefMut<'a, <$C as Component>::Container>),*
 	);
 	fn borrow_containers_mut(group: &Group) -> Result<Self::ContainersRefMut<'_>, MissingDependency> {
 		Ok(($( group.borrow_container_mut::<$C>().ok_or(MissingDependency(<$C as Component>::ID))? ),*))
@@ -1,20 +1,57 @@
-use std::{
-	any::Any,
-	cell::{Ref, RefMut},
-	hash::Hash,
-};
+use std::{any::Any, collections::HashMap, hash::Hash};
 
 pub use bitvec::{slice::BitSlice, vec::BitVec};
 pub use indexmap::IndexSet;
+use nohash_hasher::BuildNoHashHasher;
 pub use one_or_many::OneOrMany;
+pub use ure_data_derive::Container;
+
+use crate::{
+	cell::{MaybeSync, Ref, RefMut},
+	components::{Component, ComponentGroup, NewArgs},
+	glob::ContMut,
+};
 
-pub trait Container: Any {
+pub trait Container: Any + MaybeSync {
 	type Ref<'a>;
 	type RefMut<'a>;
 
 	fn as_ref<'a>(cont: Ref<'a, Self>) -> Self::Ref<'a>;
 	fn as_mut<'a>(cont: RefMut<'a, Self>) -> Self::RefMut<'a>;
 	fn delete(&mut self, indices: &[usize]);
+	/// Removes every row, `len` being the number of rows in the group.
+	/// Defaults to deleting each row, containers that can empty themselves at
+	/// once should override it.
+	fn clear(&mut self, len: usize) {
+		let indices: Vec<usize> = (0..len).rev().collect();
+		self.delete(&indices);
+	}
+	/// Reorders rows so row `i` holds what was at row `order[i]`. `order` is
+	/// a permutation of every row.
+	///
+	/// Containers holding one value per row can swap them with
+	/// [permute_by_swaps].
+	fn permute(&mut self, order: &[usize]);
+	/// Number of rows held, or [None] if the container is not one value per
+	/// row.
+	fn row_count(&self) -> Option<usize> {
+		None
+	}
+	/// Estimated bytes used, not counting what the values own.
+	fn memory_usage(&self) -> usize {
+		size_of_val(self)
+	}
+	/// Rows that fit without reallocating, or [None] if the container does
+	/// not allocate per row.
+	fn capacity(&self) -> Option<usize> {
+		None
+	}
+	/// Makes room for at least `additional` more rows.
+	fn reserve(&mut self, additional: usize) {
+		_ = additional;
+	}
+	/// Frees memory beyond what the rows held need.
+	fn shrink_to_fit(&mut self) {}
 }
 pub trait NewDefault: Container {
 	fn new_default(&mut self, num: usize);
@@ -25,9 +62,27 @@ pub trait NewWith: Container {
 	fn new_with(&mut self, args: Self::Args);
 }
 
+/// `new` method of components created from their `NewArg` through [NewWith],
+/// or through [NewDefault] without one.
+///
+/// ```ignore
+/// component!(pub Timers: SparseSet<Timer>, new_with_or_default::<Timers>, Vec<Option<Timer>>);
+/// ```
+pub fn new_with_or_default<C>(ContMut(mut container): ContMut<C>, args: &mut NewArgs)
+where
+	C: Component,
+	for<'a> C: ComponentGroup<ContainersRefMut<'a> = RefMut<'a, C::Container>>,
+	C::Container: NewWith<Args = C::NewArg> + NewDefault,
+{
+	match args.take::<C>() {
+		Some(arg) => container.new_with(arg),
+		None => container.new_default(args.len()),
+	}
+}
+
 #[derive(Debug, Default)]
 pub struct One<T: 'static>(pub T);
-impl<T: 'static> Container for One<T> {
+impl<T: MaybeSync + 'static> Container for One<T> {
 	type Ref<'a> = Ref<'a, T>;
 	type RefMut<'a> = RefMut<'a, T>;
 
@@ -38,16 +93,17 @@ impl<T: 'static> Container for One<T> {
 		RefMut::map(cont, |c| &mut c.0)
 	}
 	fn delete(&mut self, _: &[usize]) {}
+	fn permute(&mut self, _: &[usize]) {}
 }
-impl<T: 'static> NewDefault for One<T> {
+impl<T: MaybeSync + 'static> NewDefault for One<T> {
 	fn new_default(&mut self, _: usize) {}
 }
-impl<T: 'static> NewWith for One<T> {
+impl<T: MaybeSync + 'static> NewWith for One<T> {
 	type Args = ();
 
 	fn new_with(&mut self, _: Self::Args) {}
 }
-impl<T: 'static> Container for Option<T> {
+impl<T: MaybeSync + 'static> Container for Option<T> {
 	type Ref<'a> = Option<Ref<'a, T>>;
 	type RefMut<'a> = Option<RefMut<'a, T>>;
 
@@ -66,16 +122,17 @@ impl<T: 'static> Container for Option<T> {
 		}
 	}
 	fn delete(&mut self, _: &[usize]) {}
+	fn permute(&mut self, _: &[usize]) {}
 }
-impl<T: 'static> NewDefault for Option<T> {
+impl<T: MaybeSync + 'static> NewDefault for Option<T> {
 	fn new_default(&mut self, _: usize) {}
 }
-impl<T: 'static> NewWith for Option<T> {
+impl<T: MaybeSync + 'static> NewWith for Option<T> {
 	type Args = ();
 
 	fn new_with(&mut self, _: Self::Args) {}
 }
-impl<T: 'static> Container for Vec<T> {
+impl<T: MaybeSync + 'static> Container for Vec<T> {
 	type Ref<'a> = Ref<'a, [T]>;
 	type RefMut<'a> = RefMut<'a, [T]>;
 
@@ -90,23 +147,41 @@ impl<T: 'static> Container for Vec<T> {
 			self.swap_remove(index);
 		}
 	}
+	fn clear(&mut self, _: usize) {
+		Vec::clear(self);
+	}
+	fn permute(&mut self, order: &[usize]) {
+		permute_by_swaps(order, |a, b| self.swap(a, b));
+	}
+	fn row_count(&self) -> Option<usize> {
+		Some(self.len())
+	}
+	fn memory_usage(&self) -> usize {
+		size_of::<Self>() + Vec::capacity(self) * size_of::<T>()
+	}
+	fn capacity(&self) -> Option<usize> {
+		Some(Vec::capacity(self))
+	}
+	fn reserve(&mut self, additional: usize) {
+		Vec::reserve(self, additional);
+	}
+	fn shrink_to_fit(&mut self) {
+		Vec::shrink_to_fit(self);
+	}
 }
-impl<T: 'static + Default> NewDefault for Vec<T> {
+impl<T: MaybeSync + 'static + Default> NewDefault for Vec<T> {
 	fn new_default(&mut self, num: usize) {
-		self.reserve(num);
-		for _ in 0..num {
-			self.push(Default::default());
-		}
+		self.resize_with(self.len() + num, T::default);
 	}
 }
-impl<T: 'static> NewWith for Vec<T> {
+impl<T: MaybeSync + 'static> NewWith for Vec<T> {
 	type Args = Vec<T>;
 
 	fn new_with(&mut self, args: Self::Args) {
 		self.extend(args);
 	}
 }
-impl<T: 'static + Hash + Eq> Container for IndexSet<T> {
+impl<T: MaybeSync + 'static + Hash + Eq> Container for IndexSet<T> {
 	type Ref<'a> = Ref<'a, Self>;
 	type RefMut<'a> = RefMut<'a, Self>;
 
@@ -121,8 +196,30 @@ impl<T: 'static + Hash + Eq> Container for IndexSet<T> {
 			self.swap_remove_index(index);
 		}
 	}
+	fn clear(&mut self, _: usize) {
+		IndexSet::clear(self);
+	}
+	fn permute(&mut self, order: &[usize]) {
+		permute_by_swaps(order, |a, b| self.swap_indices(a, b));
+	}
+	fn row_count(&self) -> Option<usize> {
+		Some(self.len())
+	}
+	fn memory_usage(&self) -> usize {
+		// Each entry also costs a hash and an index slot.
+		size_of::<Self>() + IndexSet::capacity(self) * (size_of::<T>() + 2 * size_of::<usize>())
+	}
+	fn capacity(&self) -> Option<usize> {
+		Some(IndexSet::capacity(self))
+	}
+	fn reserve(&mut self, additional: usize) {
+		IndexSet::reserve(self, additional);
+	}
+	fn shrink_to_fit(&mut self) {
+		IndexSet::shrink_to_fit(self);
+	}
 }
-impl<T: 'static + Hash + Eq> NewWith for IndexSet<T> {
+impl<T: MaybeSync + 'static + Hash + Eq> NewWith for IndexSet<T> {
 	type Args = IndexSet<T>;
 
 	fn new_with(&mut self, args: Self::Args) {
@@ -143,7 +240,7 @@ pub enum RefOrSliceMut<'a, T> {
 	#[default]
 	None,
 }
-impl<T: 'static> Container for OneOrMany<T> {
+impl<T: MaybeSync + 'static> Container for OneOrMany<T> {
 	type Ref<'a> = RefOrSlice<'a, T>;
 	type RefMut<'a> = RefOrSliceMut<'a, T>;
 
@@ -182,15 +279,56 @@ impl<T: 'static> Container for OneOrMany<T> {
 			vec.delete(indices);
 		}
 	}
+	fn clear(&mut self, _: usize) {
+		if let OneOrMany::Many(items) = self {
+			items.clear();
+		}
+	}
+	fn permute(&mut self, order: &[usize]) {
+		if let OneOrMany::Many(items) = self {
+			items.permute(order);
+		}
+	}
+	fn row_count(&self) -> Option<usize> {
+		// A single value is shared by every row.
+		match self {
+			OneOrMany::Many(items) => Some(items.len()),
+			_ => None,
+		}
+	}
+	fn memory_usage(&self) -> usize {
+		size_of::<Self>()
+			+ match self {
+				OneOrMany::One(_) => size_of::<T>(),
+				OneOrMany::Many(items) => items.capacity() * size_of::<T>(),
+				OneOrMany::None => 0,
+			}
+	}
+	fn capacity(&self) -> Option<usize> {
+		match self {
+			OneOrMany::Many(items) => Some(items.capacity()),
+			_ => None,
+		}
+	}
+	fn reserve(&mut self, additional: usize) {
+		if let OneOrMany::Many(items) = self {
+			items.reserve(additional);
+		}
+	}
+	fn shrink_to_fit(&mut self) {
+		if let OneOrMany::Many(items) = self {
+			items.shrink_to_fit();
+		}
+	}
 }
-impl<T: 'static + Default> NewDefault for OneOrMany<T> {
+impl<T: MaybeSync + 'static + Default> NewDefault for OneOrMany<T> {
 	fn new_default(&mut self, num: usize) {
 		if let OneOrMany::Many(vec) = self {
 			vec.new_default(num);
 		}
 	}
 }
-impl<T: 'static> NewWith for OneOrMany<T> {
+impl<T: MaybeSync + 'static> NewWith for OneOrMany<T> {
 	type Args = Vec<T>;
 
 	fn new_with(&mut self, args: Self::Args) {
@@ -214,13 +352,31 @@ impl Container for BitVec {
 			self.swap_remove(index);
 		}
 	}
+	fn clear(&mut self, _: usize) {
+		BitVec::clear(self);
+	}
+	fn permute(&mut self, order: &[usize]) {
+		permute_by_swaps(order, |a, b| self.swap(a, b));
+	}
+	fn row_count(&self) -> Option<usize> {
+		Some(self.len())
+	}
+	fn memory_usage(&self) -> usize {
+		size_of::<Self>() + BitVec::capacity(self) / 8
+	}
+	fn capacity(&self) -> Option<usize> {
+		Some(BitVec::capacity(self))
+	}
+	fn reserve(&mut self, additional: usize) {
+		BitVec::reserve(self, additional);
+	}
+	fn shrink_to_fit(&mut self) {
+		BitVec::shrink_to_fit(self);
+	}
 }
 impl NewDefault for BitVec {
 	fn new_default(&mut self, num: usize) {
-		self.reserve(num);
-		for _ in 0..num {
-			self.push(Default::default());
-		}
+		self.resize(self.len() + num, false);
 	}
 }
 impl NewWith for BitVec {
@@ -230,3 +386,512 @@ impl NewWith for BitVec {
 		self.extend(args);
 	}
 }
+
+/// Applies `order` as in [Container::permute] through swaps, for containers
+/// that can swap two rows in place.
+pub fn permute_by_swaps(order: &[usize], mut swap: impl FnMut(usize, usize)) {
+	let mut done: BitVec = BitVec::repeat(false, order.len());
+	for start in 0..order.len() {
+		if done[start] {
+			continue;
+		}
+		// Pull each row of the cycle into place, the row at `start` ends up
+		// where the cycle closes.
+		let mut row = start;
+		loop {
+			done.set(row, true);
+			let from = order[row];
+			if from == start {
+				break;
+			}
+			swap(row, from);
+			row = from;
+		}
+	}
+}
+
+/// Sparse storage for components only a few rows have.
+///
+/// Every row costs one slot in the sparse index, values are packed densely.
+#[derive(Debug)]
+pub struct SparseSet<T> {
+	sparse: Vec<Option<usize>>,
+	rows: Vec<usize>,
+	values: Vec<T>,
+}
+impl<T> Default for SparseSet<T> {
+	fn default() -> Self {
+		Self {
+			sparse: Vec::new(),
+			rows: Vec::new(),
+			values: Vec::new(),
+		}
+	}
+}
+impl<T> SparseSet<T> {
+	/// Number of rows tracked, with or without a value.
+	pub fn rows(&self) -> usize {
+		self.sparse.len()
+	}
+	/// Number of rows holding a value.
+	pub fn count(&self) -> usize {
+		self.values.len()
+	}
+	pub fn contains(&self, row: usize) -> bool {
+		matches!(self.sparse.get(row), Some(Some(_)))
+	}
+	pub fn get(&self, row: usize) -> Option<&T> {
+		Some(&self.values[(*self.sparse.get(row)?)?])
+	}
+	pub fn get_mut(&mut self, row: usize) -> Option<&mut T> {
+		Some(&mut self.values[(*self.sparse.get(row)?)?])
+	}
+	/// Panics if `row` is out of bounds.
+	pub fn insert(&mut self, row: usize, value: T) -> Option<T> {
+		if let Some(dense) = self.sparse[row] {
+			return Some(std::mem::replace(&mut self.values[dense], value));
+		}
+		self.sparse[row] = Some(self.values.len());
+		self.rows.push(row);
+		self.values.push(value);
+		None
+	}
+	pub fn remove(&mut self, row: usize) -> Option<T> {
+		let dense = self.sparse.get_mut(row)?.take()?;
+		self.rows.swap_remove(dense);
+		let value = self.values.swap_remove(dense);
+		if let Some(&moved) = self.rows.get(dense) {
+			self.sparse[moved] = Some(dense);
+		}
+		Some(value)
+	}
+	pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+		self.rows.iter().copied().zip(self.values.iter())
+	}
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+		self.rows.iter().copied().zip(self.values.iter_mut())
+	}
+}
+impl<T: MaybeSync + 'static> Container for SparseSet<T> {
+	type Ref<'a> = Ref<'a, Self>;
+	type RefMut<'a> = RefMut<'a, Self>;
+
+	fn as_ref<'a>(cont: Ref<'a, Self>) -> Self::Ref<'a> {
+		cont
+	}
+	fn as_mut<'a>(cont: RefMut<'a, Self>) -> Self::RefMut<'a> {
+		cont
+	}
+	fn delete(&mut self, indices: &[usize]) {
+		for &index in indices {
+			self.remove(index);
+			self.sparse.swap_remove(index);
+			if let Some(Some(dense)) = self.sparse.get(index) {
+				self.rows[*dense] = index;
+			}
+		}
+	}
+	fn clear(&mut self, _: usize) {
+		self.sparse.clear();
+		self.rows.clear();
+		self.values.clear();
+	}
+	fn permute(&mut self, order: &[usize]) {
+		permute_by_swaps(order, |a, b| self.sparse.swap(a, b));
+		for (row, dense) in self.sparse.iter().enumerate() {
+			if let Some(dense) = dense {
+				self.rows[*dense] = row;
+			}
+		}
+	}
+	fn row_count(&self) -> Option<usize> {
+		Some(self.rows())
+	}
+	fn memory_usage(&self) -> usize {
+		size_of::<Self>()
+			+ self.sparse.capacity() * size_of::<Option<usize>>()
+			+ self.rows.capacity() * size_of::<usize>()
+			+ self.values.capacity() * size_of::<T>()
+	}
+	/// Rows tracked without reallocating the sparse index.
+	fn capacity(&self) -> Option<usize> {
+		Some(self.sparse.capacity())
+	}
+	fn reserve(&mut self, additional: usize) {
+		self.sparse.reserve(additional);
+	}
+	fn shrink_to_fit(&mut self) {
+		self.sparse.shrink_to_fit();
+		self.rows.shrink_to_fit();
+		self.values.shrink_to_fit();
+	}
+}
+impl<T: MaybeSync + 'static> NewDefault for SparseSet<T> {
+	fn new_default(&mut self, num: usize) {
+		self.sparse.resize(self.sparse.len() + num, None);
+	}
+}
+impl<T: MaybeSync + 'static> NewWith for SparseSet<T> {
+	type Args = Vec<Option<T>>;
+
+	fn new_with(&mut self, args: Self::Args) {
+		self.sparse.reserve(args.len());
+		for value in args {
+			self.sparse.push(None);
+			if let Some(value) = value {
+				self.insert(self.sparse.len() - 1, value);
+			}
+		}
+	}
+}
+
+/// Sparse storage backed by a `HashMap<usize, T>` keyed by row.
+///
+/// Unlike [SparseSet] rows without a value cost nothing.
+#[derive(Debug)]
+pub struct SparseMap<T> {
+	len: usize,
+	map: HashMap<usize, T, BuildNoHashHasher<usize>>,
+}
+impl<T> Default for SparseMap<T> {
+	fn default() -> Self {
+		Self {
+			len: 0,
+			map: Default::default(),
+		}
+	}
+}
+impl<T> SparseMap<T> {
+	/// Number of rows tracked, with or without a value.
+	pub fn rows(&self) -> usize {
+		self.len
+	}
+	/// Number of rows holding a value.
+	pub fn count(&self) -> usize {
+		self.map.len()
+	}
+	pub fn contains(&self, row: usize) -> bool {
+		self.map.contains_key(&row)
+	}
+	pub fn get(&self, row: usize) -> Option<&T> {
+		self.map.get(&row)
+	}
+	pub fn get_mut(&mut self, row: usize) -> Option<&mut T> {
+		self.map.get_mut(&row)
+	}
+	/// Panics if `row` is out of bounds.
+	pub fn insert(&mut self, row: usize, value: T) -> Option<T> {
+		assert!(row < self.len, "row {row} out of bounds ({})", self.len);
+		self.map.insert(row, value)
+	}
+	pub fn remove(&mut self, row: usize) -> Option<T> {
+		self.map.remove(&row)
+	}
+	/// Iterates in arbitrary order.
+	pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+		self.map.iter().map(|(&row, value)| (row, value))
+	}
+	/// Iterates in arbitrary order.
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+		self.map.iter_mut().map(|(&row, value)| (row, value))
+	}
+}
+impl<T: MaybeSync + 'static> Container for SparseMap<T> {
+	type Ref<'a> = Ref<'a, Self>;
+	type RefMut<'a> = RefMut<'a, Self>;
+
+	fn as_ref<'a>(cont: Ref<'a, Self>) -> Self::Ref<'a> {
+		cont
+	}
+	fn as_mut<'a>(cont: RefMut<'a, Self>) -> Self::RefMut<'a> {
+		cont
+	}
+	fn delete(&mut self, indices: &[usize]) {
+		for &index in indices {
+			self.map.remove(&index);
+			self.len -= 1;
+			if let Some(value) = self.map.remove(&self.len) {
+				self.map.insert(index, value);
+			}
+		}
+	}
+	fn clear(&mut self, _: usize) {
+		self.len = 0;
+		self.map.clear();
+	}
+	fn permute(&mut self, order: &[usize]) {
+		let mut new_rows = vec![0; order.len()];
+		for (new, &old) in order.iter().enumerate() {
+			new_rows[old] = new;
+		}
+		self.map = self
+			.map
+			.drain()
+			.map(|(old, value)| (new_rows[old], value))
+			.collect();
+	}
+	fn row_count(&self) -> Option<usize> {
+		Some(self.len)
+	}
+	fn memory_usage(&self) -> usize {
+		size_of::<Self>() + self.map.capacity() * (size_of::<usize>() + size_of::<T>())
+	}
+	fn shrink_to_fit(&mut self) {
+		self.map.shrink_to_fit();
+	}
+}
+impl<T: MaybeSync + 'static> NewDefault for SparseMap<T> {
+	fn new_default(&mut self, num: usize) {
+		self.len += num;
+	}
+}
+impl<T: MaybeSync + 'static> NewWith for SparseMap<T> {
+	type Args = Vec<Option<T>>;
+
+	fn new_with(&mut self, args: Self::Args) {
+		for value in args {
+			if let Some(value) = value {
+				self.map.insert(self.len, value);
+			}
+			self.len += 1;
+		}
+	}
+}
+
+/// A [Container] whose contents can be copied out and restored, see
+/// [Data::snapshot](crate::group::Data::snapshot).
+pub trait Snapshot: Container + Sized {
+	fn snapshot(&self) -> Self;
+	/// Overwrites the contents with `snapshot`, reusing allocations.
+	fn restore(&mut self, snapshot: &Self);
+	/// Estimated bytes held by a snapshot, not counting what the values own.
+	fn snapshot_size(&self) -> usize;
+}
+impl<T: Clone + MaybeSync + 'static> Snapshot for One<T> {
+	fn snapshot(&self) -> Self {
+		Self(self.0.clone())
+	}
+	fn restore(&mut self, snapshot: &Self) {
+		self.0.clone_from(&snapshot.0);
+	}
+	fn snapshot_size(&self) -> usize {
+		size_of::<T>()
+	}
+}
+impl<T: Clone + MaybeSync + 'static> Snapshot for Option<T> {
+	fn snapshot(&self) -> Self {
+		self.clone()
+	}
+	fn restore(&mut self, snapshot: &Self) {
+		self.clone_from(snapshot);
+	}
+	fn snapshot_size(&self) -> usize {
+		size_of::<Self>()
+	}
+}
+impl<T: Clone + MaybeSync + 'static> Snapshot for Vec<T> {
+	fn snapshot(&self) -> Self {
+		self.clone()
+	}
+	fn restore(&mut self, snapshot: &Self) {
+		self.clone_from(snapshot);
+	}
+	fn snapshot_size(&self) -> usize {
+		self.len() * size_of::<T>()
+	}
+}
+impl<T: Clone + Hash + Eq + MaybeSync + 'static> Snapshot for IndexSet<T> {
+	fn snapshot(&self) -> Self {
+		self.clone()
+	}
+	fn restore(&mut self, snapshot: &Self) {
+		self.clone_from(snapshot);
+	}
+	fn snapshot_size(&self) -> usize {
+		// Each entry also costs a hash and an index slot.
+		self.len() * (size_of::<T>() + 2 * size_of::<usize>())
+	}
+}
+impl<T: Clone + MaybeSync + 'static> Snapshot for OneOrMany<T> {
+	fn snapshot(&self) -> Self {
+		match self {
+			OneOrMany::One(item) => OneOrMany::One(item.clone()),
+			OneOrMany::Many(items) => OneOrMany::Many(items.clone()),
+			OneOrMany::None => OneOrMany::None,
+		}
+	}
+	fn restore(&mut self, snapshot: &Self) {
+		match (self, snapshot) {
+			(OneOrMany::One(item), OneOrMany::One(other)) => item.clone_from(other),
+			(OneOrMany::Many(items), OneOrMany::Many(other)) => items.clone_from(other),
+			(this, snapshot) => *this = snapshot.snapshot(),
+		}
+	}
+	fn snapshot_size(&self) -> usize {
+		match self {
+			OneOrMany::One(_) => size_of::<T>(),
+			OneOrMany::Many(items) => items.len() * size_of::<T>(),
+			OneOrMany::None => 0,
+		}
+	}
+}
+impl Snapshot for BitVec {
+	fn snapshot(&self) -> Self {
+		self.clone()
+	}
+	fn restore(&mut self, snapshot: &Self) {
+		self.clone_from(snapshot);
+	}
+	fn snapshot_size(&self) -> usize {
+		size_of_val(self.as_raw_slice())
+	}
+}
+impl<T: Clone + MaybeSync + 'static> Snapshot for SparseSet<T> {
+	fn snapshot(&self) -> Self {
+		Self {
+			sparse: self.sparse.clone(),
+			rows: self.rows.clone(),
+			values: self.values.clone(),
+		}
+	}
+	fn restore(&mut self, snapshot: &Self) {
+		self.sparse.clone_from(&snapshot.sparse);
+		self.rows.clone_from(&snapshot.rows);
+		self.values.clone_from(&snapshot.values);
+	}
+	fn snapshot_size(&self) -> usize {
+		self.sparse.len() * size_of::<Option<usize>>()
+			+ self.rows.len() * size_of::<usize>()
+			+ self.values.len() * size_of::<T>()
+	}
+}
+impl<T: Clone + MaybeSync + 'static> Snapshot for SparseMap<T> {
+	fn snapshot(&self) -> Self {
+		Self {
+			len: self.len,
+			map: self.map.clone(),
+		}
+	}
+	fn restore(&mut self, snapshot: &Self) {
+		self.len = snapshot.len;
+		self.map.clone_from(&snapshot.map);
+	}
+	fn snapshot_size(&self) -> usize {
+		self.map.len() * (size_of::<usize>() + size_of::<T>())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Container, NewDefault, NewWith, SparseMap, SparseSet, new_with_or_default};
+	use crate::{component, group::Group};
+
+	#[derive(Default, Container)]
+	struct Pair {
+		a: u8,
+		b: u16,
+	}
+
+	#[test]
+	fn derive_inside_the_crate() {
+		let mut pairs = PairSoa::default();
+		pairs.new_default(2);
+		pairs.a[1] = 1;
+		pairs.delete(&[0]);
+		assert_eq!(pairs.a, [1]);
+		assert_eq!(pairs.b, [0]);
+	}
+
+	fn set(values: &[Option<char>]) -> SparseSet<char> {
+		let mut set = SparseSet::default();
+		set.new_with(values.to_vec());
+		set
+	}
+	fn map(values: &[Option<char>]) -> SparseMap<char> {
+		let mut map = SparseMap::default();
+		map.new_with(values.to_vec());
+		map
+	}
+	/// Every row's value, checking the dense values point back to their row.
+	fn set_rows(set: &SparseSet<char>) -> Vec<Option<char>> {
+		for (row, value) in set.iter() {
+			assert_eq!(set.get(row), Some(value), "row {row}");
+		}
+		assert_eq!(set.iter().count(), set.count());
+		(0..set.rows()).map(|row| set.get(row).copied()).collect()
+	}
+	fn map_rows(map: &SparseMap<char>) -> Vec<Option<char>> {
+		assert!(map.iter().all(|(row, _)| row < map.rows()));
+		(0..map.rows()).map(|row| map.get(row).copied()).collect()
+	}
+
+	#[test]
+	fn sparse_delete_moves_the_last_row() {
+		let values = [Some('a'), None, Some('b'), Some('c')];
+		let mut sparse = set(&values);
+		sparse.delete(&[1]);
+		assert_eq!(set_rows(&sparse), [Some('a'), Some('c'), Some('b')]);
+		sparse.delete(&[0]);
+		assert_eq!(set_rows(&sparse), [Some('b'), Some('c')]);
+		sparse.delete(&[1, 0]);
+		assert_eq!(set_rows(&sparse), []);
+
+		let mut sparse = map(&values);
+		sparse.delete(&[1]);
+		assert_eq!(map_rows(&sparse), [Some('a'), Some('c'), Some('b')]);
+		sparse.delete(&[0]);
+		assert_eq!(map_rows(&sparse), [Some('b'), Some('c')]);
+		sparse.delete(&[1, 0]);
+		assert_eq!(map_rows(&sparse), []);
+	}
+
+	#[test]
+	fn sparse_delete_into_an_empty_row() {
+		let values = [None, Some('a'), None];
+		let mut sparse = set(&values);
+		sparse.delete(&[1]);
+		assert_eq!(set_rows(&sparse), [None, None]);
+		sparse.insert(1, 'b');
+		sparse.delete(&[0]);
+		assert_eq!(set_rows(&sparse), [Some('b')]);
+
+		let mut sparse = map(&values);
+		sparse.delete(&[1]);
+		assert_eq!(map_rows(&sparse), [None, None]);
+		sparse.insert(1, 'b');
+		sparse.delete(&[0]);
+		assert_eq!(map_rows(&sparse), [Some('b')]);
+	}
+
+	#[test]
+	fn sparse_permute() {
+		let values = [Some('a'), None, Some('b'), Some('c')];
+		let order = [2, 0, 3, 1];
+		let permuted = [Some('b'), Some('a'), Some('c'), None];
+		let mut sparse = set(&values);
+		sparse.permute(&order);
+		assert_eq!(set_rows(&sparse), permuted);
+		sparse.delete(&[0]);
+		assert_eq!(set_rows(&sparse), [None, Some('a'), Some('c')]);
+
+		let mut sparse = map(&values);
+		sparse.permute(&order);
+		assert_eq!(map_rows(&sparse), permuted);
+	}
+
+	component!(Letters: SparseSet<char>, new_with_or_default::<Letters>, Vec<Option<char>>);
+
+	#[test]
+	fn new_with_or_default_takes_the_argument() {
+		let mut group = Group::default();
+		group.add_component::<Letters>().unwrap();
+		group
+			.new(2)
+			.with::<Letters>(vec![None, Some('a')])
+			.done()
+			.unwrap();
+		group.new(2).done().unwrap();
+		let letters = group.borrow_container::<Letters>().unwrap();
+		assert_eq!(set_rows(&letters), [None, Some('a'), None, None]);
+	}
+}
@@ -1,6 +1,4 @@
 use std::{
-	cell::{Ref, RefMut},
-	collections::HashMap,
 	convert::Infallible,
 	error::Error,
 	fmt::Display,
@@ -8,16 +6,27 @@ use std::{
 	marker::PhantomData,
 };
 
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
+use slotmap::KeyData;
 
 use crate::{
-	components::{Component, ComponentDependency, ComponentGroup, ComponentId, MissingDependency},
-	group::{Data, Group},
+	cell::{MaybeSync, Ref, RefMut},
+	components::{
+		Component, ComponentDependency, ComponentGroup, ComponentId, MissingDependency, NewArgs,
+		NewArgsError,
+	},
+	group::{Data, Group, NewWithArgs, PermuteError},
 	method::{MethodTrait, TryFromGlob},
+	resource::{MissingResource, Resources},
+	signals::{SignalError, SignalId},
 };
 
+/// Items spread over several groups, keyed by group.
+///
+/// Groups are iterated in the order they were added, so iteration is the same
+/// on every run given the same sequence of calls.
 pub struct Glob<GroupKey: slotmap::Key, ItemKey, C: Component<Container = IndexSet<ItemKey>>> {
-	items: HashMap<GroupKey, Option<Vec<ItemKey>>>,
+	items: IndexMap<GroupKey, Option<Vec<ItemKey>>>,
 	_marker: PhantomData<C>,
 }
 pub struct Globule<GroupKey: slotmap::Key, ItemKey> {
@@ -25,62 +34,140 @@ pub struct Globule<GroupKey: slotmap::Key, ItemKey> {
 	indices: Option<Vec<ItemKey>>,
 }
 pub struct GlobuleIndexed<'a> {
-	group: Ref<'a, Group>,
-	indices: Option<Vec<usize>>,
+	pub(crate) group: Ref<'a, Group>,
+	pub(crate) indices: Option<Vec<usize>>,
+	pub(crate) resources: Option<&'a Resources>,
+	pub(crate) key: Option<KeyData>,
 }
 impl<'a> GlobuleIndexed<'a> {
-	pub fn as_ref<'b>(&'a self) -> GlobuleRef<'a, 'b>
-	where
-		'a: 'b,
-	{
+	pub fn as_ref(&self) -> GlobuleRef<'_, '_> {
 		GlobuleRef {
 			group: &self.group,
 			indices: self.indices.as_ref().map(|i| i.as_slice()),
+			resources: self.resources,
+			key: self.key,
 		}
 	}
-	pub fn group(&'a self) -> Ref<'a, Group> {
+	pub fn group(&self) -> Ref<'a, Group> {
 		Ref::clone(&self.group)
 	}
 }
 pub struct GlobuleIndexedMut<'a> {
-	group: RefMut<'a, Group>,
-	indices: Option<Vec<usize>>,
+	pub(crate) group: RefMut<'a, Group>,
+	pub(crate) indices: Option<Vec<usize>>,
+	pub(crate) resources: Option<&'a Resources>,
+	pub(crate) key: Option<KeyData>,
 }
 impl<'a> GlobuleIndexedMut<'a> {
-	pub fn as_ref<'b>(&'a self) -> GlobuleRef<'a, 'b>
-	where
-		'a: 'b,
-	{
+	pub fn as_ref(&self) -> GlobuleRef<'_, '_> {
 		GlobuleRef {
 			group: &self.group,
 			indices: self.indices.as_ref().map(|i| i.as_slice()),
+			resources: self.resources,
+			key: self.key,
 		}
 	}
-	pub fn as_mut<'b, 'c>(&'a mut self) -> GlobuleMut<'a, 'b, 'c>
-	where
-		'a: 'b,
-	{
+	pub fn as_mut(&mut self) -> GlobuleMut<'a, '_, '_> {
 		GlobuleMut {
 			group: &mut self.group,
 			indices: self.indices.as_ref().map(|i| i.as_slice()),
+			resources: self.resources,
+			key: self.key,
 		}
 	}
-	pub fn group(&'a mut self) -> &'a mut RefMut<'a, Group> {
+	pub fn group(&mut self) -> &mut RefMut<'a, Group> {
 		&mut self.group
 	}
+	/// [Group::new], with `new` methods seeing the [Data]'s resources.
+	#[allow(clippy::new_ret_no_self)]
+	pub fn new(&mut self, num: usize) -> NewWithArgs<'_> {
+		NewWithArgs {
+			group: &mut self.group,
+			args: NewArgs::new(num),
+			resources: self.resources,
+			key: self.key,
+		}
+	}
+	/// [Group::new_from_args], with `new` methods seeing the [Data]'s
+	/// resources.
+	pub fn new_from_args(&mut self, args: NewArgs) -> Result<(), NewArgsError> {
+		self.group.new_from_args_in(args, self.resources, self.key)
+	}
+	/// [Group::delete], with `delete` methods seeing the [Data]'s resources.
+	pub fn delete(&mut self, indices: &[usize]) -> Result<(), SignalError> {
+		self.group.delete_in(indices, self.resources, self.key)
+	}
+	/// [Group::clear], with `clear` methods seeing the [Data]'s resources.
+	pub fn clear(&mut self) -> Result<(), SignalError> {
+		self.group.clear_in(self.resources, self.key)
+	}
+	/// [Group::permute], with [PERMUTE](crate::group::PERMUTE) methods seeing
+	/// the [Data]'s resources.
+	pub fn permute(&mut self, order: &[usize]) -> Result<(), PermuteError> {
+		self.group.permute_in(order, self.resources, self.key)
+	}
+	/// [Group::call_signal], with connected methods seeing the [Data]'s
+	/// resources.
+	pub fn call_signal<Args>(
+		&mut self,
+		signal: &SignalId<Args>,
+		args: Args,
+	) -> Result<(), SignalError> {
+		self.group.call_signal_in(signal, args, self.resources, self.key)
+	}
 }
 #[derive(Clone, Copy)]
 pub struct GlobuleRef<'a, 'b> {
 	group: &'a Group,
 	indices: Option<&'b [usize]>,
+	resources: Option<&'a Resources>,
+	key: Option<KeyData>,
 }
 impl<'a, 'b> GlobuleRef<'a, 'b> {
 	pub fn from_group(group: &'a Group) -> Self {
 		Self {
 			group,
 			indices: None,
+			resources: None,
+			key: None,
 		}
 	}
+	/// A globule for the signals of `group`.
+	pub(crate) fn hook(
+		group: &'a Group,
+		resources: Option<&'a Resources>,
+		key: Option<KeyData>,
+	) -> Self {
+		Self {
+			group,
+			indices: None,
+			resources,
+			key,
+		}
+	}
+	pub fn with_resources(mut self, resources: &'a Resources) -> Self {
+		self.resources = Some(resources);
+		self
+	}
+	pub fn with_key(mut self, key: impl slotmap::Key) -> Self {
+		self.key = Some(key.data());
+		self
+	}
+	/// Key of the group in its [Data], if known.
+	pub fn key<Key: slotmap::Key>(&self) -> Option<Key> {
+		self.key.map(Key::from)
+	}
+	/// Looks up the group's resources first, then the [Data]'s.
+	pub fn resource<T: 'static>(&self) -> Option<Ref<'a, T>> {
+		self.group
+			.resource::<T>()
+			.or_else(|| self.resources?.get::<T>())
+	}
+	pub fn resource_mut<T: 'static>(&self) -> Option<RefMut<'a, T>> {
+		self.group
+			.resource_mut::<T>()
+			.or_else(|| self.resources?.get_mut::<T>())
+	}
 	pub fn call_method<T: TryFromGlob<'a, 'b>, Args, Return>(
 		self,
 		method: impl MethodTrait<T, Args, Return>,
@@ -95,22 +182,28 @@ impl<'a, 'b> GlobuleRef<'a, 'b> {
 pub struct GlobuleMut<'a, 'b, 'c> {
 	group: &'c mut RefMut<'a, Group>,
 	indices: Option<&'b [usize]>,
+	resources: Option<&'a Resources>,
+	key: Option<KeyData>,
 }
 impl<'a, 'b, 'c> GlobuleMut<'a, 'b, 'c> {
 	pub fn from_group(group: &'c mut RefMut<'a, Group>) -> Self {
 		Self {
 			group,
 			indices: None,
+			resources: None,
+			key: None,
 		}
 	}
-	pub fn as_ref(&'a self) -> GlobuleRef<'a, 'b> {
+	pub fn as_ref(&self) -> GlobuleRef<'_, 'b> {
 		GlobuleRef {
 			group: &self.group,
 			indices: self.indices,
+			resources: self.resources,
+			key: self.key,
 		}
 	}
-	pub fn call_method<T: TryFromGlob<'a, 'b>, Args, Return>(
-		&'a self,
+	pub fn call_method<'s, T: TryFromGlob<'s, 'b>, Args, Return>(
+		&'s self,
 		method: impl MethodTrait<T, Args, Return>,
 		args: Args,
 	) -> Result<Return, Box<dyn Error>> {
@@ -121,7 +214,7 @@ impl<'a, 'b, 'c> GlobuleMut<'a, 'b, 'c> {
 	}
 }
 
-impl<GroupKey: slotmap::Key, ItemKey: Hash + Eq + 'static, C> Glob<GroupKey, ItemKey, C>
+impl<GroupKey: slotmap::Key, ItemKey: Hash + Eq + MaybeSync + 'static, C> Glob<GroupKey, ItemKey, C>
 where
 	C: Component<Container = IndexSet<ItemKey>>,
 {
@@ -147,6 +240,8 @@ where
 		Some(GlobuleIndexed {
 			indices: self.index(&group, &group_key)?,
 			group,
+			resources: Some(data.resources()),
+			key: Some(group_key.data()),
 		})
 	}
 	pub fn get_mut<'a>(
@@ -158,11 +253,17 @@ where
 		Some(GlobuleIndexedMut {
 			indices: self.index(&group, &group_key)?,
 			group,
+			resources: Some(data.resources()),
+			key: Some(group_key.data()),
 		})
 	}
 	pub fn add_group(&mut self, group_key: GroupKey) {
 		self.items.insert(group_key, None);
 	}
+	/// Removes a group, keeping the order of the others.
+	pub fn remove_group(&mut self, group_key: GroupKey) {
+		self.items.shift_remove(&group_key);
+	}
 	pub fn iter<'a, 'b>(
 		&'a self,
 		data: &'b Data<GroupKey>,
@@ -186,11 +287,11 @@ where
 }
 
 pub struct GlobIter<'a, 'b, GroupKey: slotmap::Key, ItemKey, C> {
-	glob: std::collections::hash_map::Iter<'a, GroupKey, Option<Vec<ItemKey>>>,
+	glob: indexmap::map::Iter<'a, GroupKey, Option<Vec<ItemKey>>>,
 	data: &'b Data<GroupKey>,
 	_marker: PhantomData<C>,
 }
-impl<'a, 'b: 'a, GroupKey: slotmap::Key, ItemKey: Hash + Eq + 'static, C: Component> Iterator
+impl<'a, 'b: 'a, GroupKey: slotmap::Key, ItemKey: Hash + Eq + MaybeSync + 'static, C: Component> Iterator
 	for GlobIter<'a, 'b, GroupKey, ItemKey, C>
 where
 	C: Component<Container = IndexSet<ItemKey>>,
@@ -198,12 +299,17 @@ where
 	type Item = GlobuleIndexed<'a>;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		let (key, value) = self.glob.next()?;
-		let group = self.data.get(*key).unwrap().borrow();
+		// Groups removed from the data since being added are skipped.
+		let (key, group, value) = self
+			.glob
+			.find_map(|(key, value)| Some((key, self.data.get(*key)?, value)))?;
+		let group = group.borrow();
 		let Some(value) = value.as_ref() else {
 			return Some(GlobuleIndexed {
 				group,
 				indices: None,
+				resources: Some(self.data.resources()),
+				key: Some(key.data()),
 			});
 		};
 		let indices = {
@@ -213,16 +319,21 @@ where
 				.map(|key| component.get_index_of(key))
 				.collect()
 		};
-		Some(GlobuleIndexed { group, indices })
+		Some(GlobuleIndexed {
+			group,
+			indices,
+			resources: Some(self.data.resources()),
+			key: Some(key.data()),
+		})
 	}
 }
 
 pub struct GlobIterMut<'a, 'b, GroupKey: slotmap::Key, ItemKey, C> {
-	glob: std::collections::hash_map::Iter<'a, GroupKey, Option<Vec<ItemKey>>>,
+	glob: indexmap::map::Iter<'a, GroupKey, Option<Vec<ItemKey>>>,
 	data: &'b Data<GroupKey>,
 	_marker: PhantomData<C>,
 }
-impl<'a, 'b: 'a, GroupKey: slotmap::Key, ItemKey: Hash + Eq + 'static, C: Component> Iterator
+impl<'a, 'b: 'a, GroupKey: slotmap::Key, ItemKey: Hash + Eq + MaybeSync + 'static, C: Component> Iterator
 	for GlobIterMut<'a, 'b, GroupKey, ItemKey, C>
 where
 	C: Component<Container = IndexSet<ItemKey>>,
@@ -230,12 +341,17 @@ where
 	type Item = GlobuleIndexedMut<'a>;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		let (key, value) = self.glob.next()?;
-		let group = self.data.get(*key).unwrap().borrow_mut();
+		// Groups removed from the data since being added are skipped.
+		let (key, group, value) = self
+			.glob
+			.find_map(|(key, value)| Some((key, self.data.get(*key)?, value)))?;
+		let group = group.borrow_mut();
 		let Some(value) = value.as_ref() else {
 			return Some(GlobuleIndexedMut {
 				group,
 				indices: None,
+				resources: Some(self.data.resources()),
+				key: Some(key.data()),
 			});
 		};
 		let indices = {
@@ -245,7 +361,12 @@ where
 				.map(|key| component.get_index_of(key))
 				.collect()
 		};
-		Some(GlobuleIndexedMut { group, indices })
+		Some(GlobuleIndexedMut {
+			group,
+			indices,
+			resources: Some(self.data.resources()),
+			key: Some(key.data()),
+		})
 	}
 }
 
@@ -340,3 +461,37 @@ impl<'a, C: ComponentGroup> TryFrom<GlobuleRef<'a, '_>> for CompMut<'a, C> {
 		C::borrow_components_mut(value.group).map(|c| Self(c))
 	}
 }
+
+pub struct Res<'a, T: 'static>(pub Ref<'a, T>);
+impl<T: 'static> ComponentDependency for Res<'_, T> {
+	fn dependencies() -> Vec<ComponentId> {
+		Vec::new()
+	}
+}
+impl<'a, T: 'static> TryFrom<GlobuleRef<'a, '_>> for Res<'a, T> {
+	type Error = MissingResource;
+
+	fn try_from(value: GlobuleRef<'a, '_>) -> Result<Self, Self::Error> {
+		value
+			.resource::<T>()
+			.map(|r| Self(r))
+			.ok_or(MissingResource::of::<T>())
+	}
+}
+
+pub struct ResMut<'a, T: 'static>(pub RefMut<'a, T>);
+impl<T: 'static> ComponentDependency for ResMut<'_, T> {
+	fn dependencies() -> Vec<ComponentId> {
+		Vec::new()
+	}
+}
+impl<'a, T: 'static> TryFrom<GlobuleRef<'a, '_>> for ResMut<'a, T> {
+	type Error = MissingResource;
+
+	fn try_from(value: GlobuleRef<'a, '_>) -> Result<Self, Self::Error> {
+		value
+			.resource_mut::<T>()
+			.map(|r| Self(r))
+			.ok_or(MissingResource::of::<T>())
+	}
+}
@@ -1,26 +1,40 @@
 use std::{
-	cell::{Ref, RefCell, RefMut},
 	error::Error,
+	fmt::Display,
+	ops::{Deref, DerefMut},
 };
 
-use slotmap::SlotMap;
+use bitvec::vec::BitVec;
+use slotmap::{KeyData, SlotMap};
 
+#[cfg(feature = "reflect")]
+use crate::reflect::{FieldInfo, ReflectContainer, ReflectError, ReflectVTable, Value};
 use crate::{
-	components::{Component, ComponentId, Components, MissingDependency, NewArgs},
-	containers::Container,
-	glob::GlobuleRef,
+	cell::{MaybeSync, Ref, RefCell, RefMut},
+	components::{
+		Component, ComponentId, ComponentInfo, Components, MissingDependency, NewArgs, NewArgsError,
+	},
+	containers::{Container, Snapshot},
+	glob::{GlobuleIndexed, GlobuleIndexedMut, GlobuleRef},
 	method::{MethodTrait, TryFromGlob},
+	resource::Resources,
 	signal,
-	signals::{SignalId, Signals},
+	signals::{SignalError, SignalId, Signals},
+	snapshot::{DataSnapshot, GroupSnapshot, SnapshotError},
 };
 
-signal!(NEW: NewArgs);
-signal!(DELETE: &[usize]);
+signal!(pub NEW: NewArgs);
+signal!(pub DELETE: &[usize]);
+signal!(pub CLEAR: &[usize]);
+signal!(pub PERMUTE: &[usize]);
 
 #[derive(Default)]
 pub struct Group {
 	len: usize,
+	/// Rows being created while [NEW] runs.
+	creating: usize,
 	components: Components,
+	resources: Resources,
 	signals: Signals,
 }
 
@@ -35,19 +49,26 @@ impl Group {
 		&mut self,
 		container: C::Container,
 	) -> Result<(), MissingDependency> {
-		self.are_depencencies_satisfied(&C::dependencies())?;
+		let mut dependencies = C::dependencies();
+		dependencies.retain(|id| *id != C::ID);
+		self.are_depencencies_satisfied(&dependencies)?;
 		self.signals.connect(&NEW, C::new);
 		self.signals.connect(&DELETE, C::delete);
+		self.signals.connect(&CLEAR, C::clear);
 		self.components.add::<C>(container);
 		Ok(())
 	}
-	// pub fn connect_signal<'a, Args: Clone>(
-	// 	&mut self,
-	// 	signal_id: &SignalId<Args>,
-	// 	method: impl Into<Method<Args>>,
-	// ) {
-	// 	self.signals.connect(signal_id, method.into());
-	// }
+	/// Runs `method` every time `signal` is called on the group. Methods run
+	/// in the order they were connected, components connect theirs when added.
+	pub fn connect_signal<Args>(
+		&mut self,
+		signal: &SignalId<Args>,
+		method: impl for<'a, 'b, 'c> Fn(GlobuleRef<'a, 'b>, &'c mut Args) -> Result<(), Box<dyn Error>>
+		+ MaybeSync
+		+ 'static,
+	) {
+		self.signals.connect(signal, method);
+	}
 	// pub fn new(&mut self, num: usize) {
 	// 	self.new_args(NewArgs::new(num));
 	// }
@@ -69,14 +90,57 @@ impl Group {
 		NewWithArgs {
 			group: self,
 			args: NewArgs::new(num),
+			resources: None,
+			key: None,
 		}
 	}
-	pub fn new_from_args(&mut self, args: NewArgs) {
-		self.len += args.len();
-		self.signals.call(&NEW, self.glob(), args);
+	/// Creates `args.len()` rows. `new` methods see the length from before
+	/// the rows were added.
+	///
+	/// Nothing is created if an argument has the wrong number of rows. An
+	/// argument of the wrong type or a failed `new` method is reported after
+	/// the rows were created.
+	///
+	/// Like every hook run through the [Group] directly, `new` methods only
+	/// see the group's resources. Use [Data::globule_mut] for the [Data]'s.
+	pub fn new_from_args(&mut self, args: NewArgs) -> Result<(), NewArgsError> {
+		self.new_from_args_in(args, None, None)
+	}
+	pub(crate) fn new_from_args_in(
+		&mut self,
+		mut args: NewArgs,
+		resources: Option<&Resources>,
+		key: Option<KeyData>,
+	) -> Result<(), NewArgsError> {
+		args.check()?;
+		let len = args.len();
+		self.creating = len;
+		let signal = self
+			.signals
+			.call(&NEW, GlobuleRef::hook(self, resources, key), &mut args);
+		self.creating = 0;
+		self.len += len;
+		args.warn_unused();
+		args.check()?;
+		signal.map_err(NewArgsError::Signal)
+	}
+	/// Calls every method connected to `signal`, even after one fails.
+	pub fn call_signal<Args>(
+		&mut self,
+		signal: &SignalId<Args>,
+		args: Args,
+	) -> Result<(), SignalError> {
+		self.call_signal_in(signal, args, None, None)
 	}
-	pub fn call_signal<Args>(&mut self, signal: &SignalId<Args>, args: Args) {
-		self.signals.call(&signal, self.glob(), args);
+	pub(crate) fn call_signal_in<Args>(
+		&mut self,
+		signal: &SignalId<Args>,
+		mut args: Args,
+		resources: Option<&Resources>,
+		key: Option<KeyData>,
+	) -> Result<(), SignalError> {
+		self.signals
+			.call(signal, GlobuleRef::hook(self, resources, key), &mut args)
 	}
 	pub fn call_method<'a: 'b, 'b, T: TryFromGlob<'a, 'b>, Args, Return>(
 		&'a self,
@@ -85,13 +149,95 @@ impl Group {
 	) -> Result<Return, Box<dyn Error>> {
 		method.call_method(self.glob(), args)
 	}
-	pub fn delete(&mut self, indices: &[usize]) {
-		if indices.len() == 0 {
-			return;
+	/// Deletes rows, even if a [DELETE] method fails. The failures are
+	/// returned afterwards.
+	pub fn delete(&mut self, indices: &[usize]) -> Result<(), SignalError> {
+		self.delete_in(indices, None, None)
+	}
+	pub(crate) fn delete_in(
+		&mut self,
+		indices: &[usize],
+		resources: Option<&Resources>,
+		key: Option<KeyData>,
+	) -> Result<(), SignalError> {
+		if indices.is_empty() {
+			return Ok(());
 		}
-		self.signals
-			.call(&DELETE, GlobuleRef::from_group(&self), indices);
+		let mut rows = indices;
+		let signal = self
+			.signals
+			.call(&DELETE, GlobuleRef::hook(self, resources, key), &mut rows);
+		self.components.delete(indices);
 		self.len -= indices.len();
+		signal
+	}
+	/// Deletes every row, emptying each container at once instead of removing
+	/// rows one by one. Runs [CLEAR] rather than [DELETE].
+	pub fn clear(&mut self) -> Result<(), SignalError> {
+		self.clear_in(None, None)
+	}
+	pub(crate) fn clear_in(
+		&mut self,
+		resources: Option<&Resources>,
+		key: Option<KeyData>,
+	) -> Result<(), SignalError> {
+		if self.len == 0 {
+			return Ok(());
+		}
+		let indices: Vec<usize> = (0..self.len).collect();
+		let signal = self.signals.call(
+			&CLEAR,
+			GlobuleRef::hook(self, resources, key),
+			&mut indices.as_slice(),
+		);
+		self.components.clear(self.len);
+		self.len = 0;
+		signal
+	}
+	/// Reorders rows so row `i` holds what was at row `order[i]`, in every
+	/// container. [PERMUTE] runs afterwards with `order`, so tables of row
+	/// handles outside the group can be updated.
+	pub fn permute(&mut self, order: &[usize]) -> Result<(), PermuteError> {
+		self.permute_in(order, None, None)
+	}
+	pub(crate) fn permute_in(
+		&mut self,
+		mut order: &[usize],
+		resources: Option<&Resources>,
+		key: Option<KeyData>,
+	) -> Result<(), PermuteError> {
+		if order.len() != self.len {
+			return Err(PermuteError::Invalid);
+		}
+		let mut seen: BitVec = BitVec::repeat(false, self.len);
+		for &row in order {
+			if row >= self.len || seen.replace(row, true) {
+				return Err(PermuteError::Invalid);
+			}
+		}
+		self.components.permute(order);
+		self.signals
+			.call(&PERMUTE, GlobuleRef::hook(self, resources, key), &mut order)
+			.map_err(PermuteError::Signal)
+	}
+	/// Sorts rows by the order `method` puts the row indices in.
+	///
+	/// ```ignore
+	/// fn by_depth(CompRef(depths): CompRef<Depths>, order: &mut [usize]) {
+	///     order.sort_by(|&a, &b| depths[a].total_cmp(&depths[b]));
+	/// }
+	/// group.sort_by(|glob, order| glob.call_method(by_depth, order))?;
+	/// ```
+	pub fn sort_by(
+		&mut self,
+		method: impl for<'a, 'b> FnOnce(
+			GlobuleRef<'a, 'b>,
+			&'b mut [usize],
+		) -> Result<(), Box<dyn Error>>,
+	) -> Result<(), Box<dyn Error>> {
+		let mut order: Vec<usize> = (0..self.len).collect();
+		method(self.glob(), &mut order)?;
+		Ok(self.permute(&order)?)
 	}
 	pub fn borrow_container<C: Component>(&'_ self) -> Option<Ref<'_, C::Container>> {
 		self.components.borrow_container::<C>()
@@ -115,9 +261,69 @@ impl Group {
 	pub fn is_empty(&self) -> bool {
 		self.len == 0
 	}
+	/// Rows being created by the [NEW] methods running now, from [Group::len]
+	/// on. Reflection reaches them once their components' `new` methods ran.
+	pub fn creating(&self) -> usize {
+		self.creating
+	}
+	/// Rows the group can grow to before a container reallocates, [None] if
+	/// no container allocates per row. Containers borrowed mutably are skipped.
+	pub fn capacity(&self) -> Option<usize> {
+		self.component_infos()
+			.iter()
+			.filter_map(|info| info.capacity)
+			.min()
+	}
+	/// Makes room for at least `additional` more rows in every container.
+	pub fn reserve(&mut self, additional: usize) {
+		self.components.reserve(additional);
+	}
+	/// Frees memory held by containers beyond the group's rows, e.g. after a
+	/// level transition deleted most of them.
+	pub fn shrink_to_fit(&mut self) {
+		self.components.shrink_to_fit();
+	}
+	pub fn insert_resource<T: MaybeSync + 'static>(&mut self, value: T) -> Option<T> {
+		self.resources.insert(value)
+	}
+	pub fn resource<T: 'static>(&self) -> Option<Ref<'_, T>> {
+		self.resources.get::<T>()
+	}
+	pub fn resource_mut<T: 'static>(&self) -> Option<RefMut<'_, T>> {
+		self.resources.get_mut::<T>()
+	}
+	pub fn remove_resource<T: 'static>(&mut self) -> Option<T> {
+		self.resources.remove::<T>()
+	}
 	pub fn contains_component<C: Component>(&self) -> bool {
 		self.components.contains(&C::ID)
 	}
+	pub fn component_ids(&self) -> impl Iterator<Item = ComponentId> {
+		self.components.ids()
+	}
+	pub fn component_info(&self, id: ComponentId) -> Option<ComponentInfo> {
+		self.components.info(id)
+	}
+	pub fn component_infos(&self) -> Vec<ComponentInfo> {
+		self.components
+			.ids()
+			.filter_map(|id| self.components.info(id))
+			.collect()
+	}
+	/// Components whose containers hold a different number of rows than the
+	/// group.
+	pub fn diverging_components(&self) -> Vec<ComponentInfo> {
+		self.component_infos()
+			.into_iter()
+			.filter(|info| info.diverges(self.len))
+			.collect()
+	}
+	pub fn summary(&self) -> GroupSummary {
+		GroupSummary {
+			len: self.len,
+			components: self.component_infos(),
+		}
+	}
 	pub fn are_depencencies_satisfied(
 		&self,
 		dependencies: &[ComponentId],
@@ -132,21 +338,338 @@ impl Group {
 	pub fn glob(&self) -> GlobuleRef<'_, '_> {
 		GlobuleRef::from_group(self)
 	}
+	/// Lets [Group::snapshot] capture the container of `C`.
+	pub fn enable_snapshot<C: Component>(&mut self) -> Result<(), MissingDependency>
+	where
+		C::Container: Snapshot,
+	{
+		self.components.enable_snapshot::<C>()
+	}
+	/// Captures the group, failing if any component does not have snapshots
+	/// enabled.
+	pub fn snapshot(&self) -> Result<GroupSnapshot, SnapshotError> {
+		let (containers, size) = self.components.snapshot()?;
+		Ok(GroupSnapshot {
+			len: self.len,
+			containers,
+			size,
+		})
+	}
+	/// Restores the group, skipping containers unchanged since the snapshot.
+	pub fn restore(&mut self, snapshot: &GroupSnapshot) -> Result<(), SnapshotError> {
+		self.components.restore(&snapshot.containers)?;
+		self.len = snapshot.len;
+		Ok(())
+	}
 }
 
+#[cfg(feature = "reflect")]
+impl Group {
+	pub fn enable_reflect<C: Component>(&mut self) -> Result<(), MissingDependency>
+	where
+		C::Container: ReflectContainer,
+	{
+		self.set_reflect::<C>(ReflectVTable::of::<C::Container>())
+	}
+	/// Reflects `C` with hand written functions.
+	pub fn set_reflect<C: Component>(
+		&mut self,
+		vtable: ReflectVTable,
+	) -> Result<(), MissingDependency> {
+		self.components.set_reflect(C::ID, vtable)
+	}
+	/// A reflected component by its type name or the last segment of it.
+	pub fn reflect_id(&self, name: &str) -> Option<ComponentId> {
+		self.components
+			.reflected()
+			.find(|(_, n)| *n == name || n.rsplit("::").next() == Some(name))
+			.map(|(id, _)| id)
+	}
+	pub fn reflect_fields(&self, id: ComponentId) -> Option<Vec<FieldInfo>> {
+		Some((self.components.reflect(id)?.fields)())
+	}
+	/// Every reflected component of `row`, keyed by component name.
+	pub fn reflect_row(&self, row: usize) -> Result<Value, ReflectError> {
+		if row >= self.len + self.creating {
+			return Err(ReflectError::MissingRow(row));
+		}
+		Ok(Value::Struct(
+			self.components
+				.reflected()
+				.map(|(id, name)| {
+					let name = name.rsplit("::").next().unwrap_or(name);
+					let value = self.components.reflect_get(id, row).unwrap_or(Value::None);
+					(name.into(), value)
+				})
+				.collect(),
+		))
+	}
+	/// Reads the value at `path` in a row of a component, see [Value::path].
+	pub fn reflect_get(
+		&self,
+		id: ComponentId,
+		row: usize,
+		path: &str,
+	) -> Result<Value, ReflectError> {
+		if row >= self.len + self.creating {
+			return Err(ReflectError::MissingRow(row));
+		}
+		let value = self.components.reflect_get(id, row)?;
+		value
+			.path(path)
+			.cloned()
+			.ok_or_else(|| ReflectError::UnknownField(path.to_string()))
+	}
+	/// Writes the value at `path` in a row of a component.
+	pub fn reflect_set(
+		&self,
+		id: ComponentId,
+		row: usize,
+		path: &str,
+		value: Value,
+	) -> Result<(), ReflectError> {
+		if row >= self.len + self.creating {
+			return Err(ReflectError::MissingRow(row));
+		}
+		let mut row_value = self.components.reflect_get(id, row)?;
+		*row_value
+			.path_mut(path)
+			.ok_or_else(|| ReflectError::UnknownField(path.to_string()))? = value;
+		self.components.reflect_set(id, row, &row_value)
+	}
+}
+
+#[derive(Debug)]
+pub enum PermuteError {
+	/// The order is not a permutation of the group's rows.
+	Invalid,
+	/// Methods connected to [PERMUTE] failed. The rows were still reordered.
+	Signal(SignalError),
+}
+impl Display for PermuteError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Invalid => write!(f, "Order is not a permutation of the group's rows."),
+			Self::Signal(error) => error.fmt(f),
+		}
+	}
+}
+impl Error for PermuteError {}
+
 #[must_use]
 pub struct NewWithArgs<'a> {
-	group: &'a mut Group,
-	args: NewArgs,
+	pub(crate) group: &'a mut Group,
+	pub(crate) args: NewArgs,
+	pub(crate) resources: Option<&'a Resources>,
+	pub(crate) key: Option<KeyData>,
 }
 impl NewWithArgs<'_> {
 	pub fn with<C: Component>(mut self, arg: C::NewArg) -> Self {
 		self.args.with::<C>(arg);
 		self
 	}
-	pub fn done(self) {
-		self.group.new_from_args(self.args);
+	pub fn with_fn<C: Component, T>(mut self, f: impl FnMut(usize) -> T) -> Self
+	where
+		C::NewArg: FromIterator<T>,
+	{
+		self.args.with_fn::<C, T>(f);
+		self
+	}
+	pub fn done(self) -> Result<(), NewArgsError> {
+		self.group
+			.new_from_args_in(self.args, self.resources, self.key)
+	}
+}
+
+/// Every [Group] of a game, plus resources shared by all of them.
+///
+/// Derefs to the underlying [SlotMap] of groups. Its iteration order only
+/// depends on the sequence of inserts and removes, never on the run.
+///
+/// With the `sync` feature it is `Send + Sync`, see [crate::cell].
+pub struct Data<Key: slotmap::Key> {
+	groups: SlotMap<Key, RefCell<Group>>,
+	resources: Resources,
+}
+impl<Key: slotmap::Key> Default for Data<Key> {
+	fn default() -> Self {
+		Self {
+			groups: SlotMap::with_key(),
+			resources: Default::default(),
+		}
+	}
+}
+impl<Key: slotmap::Key> Deref for Data<Key> {
+	type Target = SlotMap<Key, RefCell<Group>>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.groups
+	}
+}
+impl<Key: slotmap::Key> DerefMut for Data<Key> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.groups
+	}
+}
+#[cfg(feature = "sync")]
+const _: () = {
+	fn assert_sync<T: Send + Sync>() {}
+	#[allow(dead_code)]
+	fn data_is_sync<Key: slotmap::Key>() {
+		assert_sync::<Data<Key>>();
+	}
+};
+impl<Key: slotmap::Key> Data<Key> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	pub fn insert_resource<T: MaybeSync + 'static>(&mut self, value: T) -> Option<T> {
+		self.resources.insert(value)
+	}
+	pub fn resource<T: 'static>(&self) -> Option<Ref<'_, T>> {
+		self.resources.get::<T>()
+	}
+	pub fn resource_mut<T: 'static>(&self) -> Option<RefMut<'_, T>> {
+		self.resources.get_mut::<T>()
+	}
+	pub fn remove_resource<T: 'static>(&mut self) -> Option<T> {
+		self.resources.remove::<T>()
+	}
+	pub fn resources(&self) -> &Resources {
+		&self.resources
+	}
+	/// Borrows a group with access to the shared resources.
+	pub fn globule(&self, key: Key) -> Option<GlobuleIndexed<'_>> {
+		Some(GlobuleIndexed {
+			group: self.groups.get(key)?.borrow(),
+			indices: None,
+			resources: Some(&self.resources),
+			key: Some(key.data()),
+		})
+	}
+	/// Mutably borrows a group with access to the shared resources, which
+	/// hooks also see when rows are created or deleted through it.
+	pub fn globule_mut(&self, key: Key) -> Option<GlobuleIndexedMut<'_>> {
+		Some(GlobuleIndexedMut {
+			group: self.groups.get(key)?.borrow_mut(),
+			indices: None,
+			resources: Some(&self.resources),
+			key: Some(key.data()),
+		})
+	}
+	/// Summarizes every group, skipping those currently borrowed mutably.
+	pub fn summary(&self) -> DataSummary<Key> {
+		DataSummary {
+			groups: self
+				.groups
+				.iter()
+				.filter_map(|(key, group)| Some((key, group.try_borrow().ok()?.summary())))
+				.collect(),
+		}
+	}
+	/// Captures every group, failing if any component of any group does not
+	/// have snapshots enabled.
+	pub fn snapshot(&self) -> Result<DataSnapshot<Key>, SnapshotError> {
+		let groups = self
+			.groups
+			.iter()
+			.map(|(key, group)| match group.borrow().snapshot() {
+				Ok(snapshot) => Ok((key, snapshot)),
+				Err(SnapshotError::NotSnapshot(component)) => {
+					Err(SnapshotError::GroupNotSnapshot {
+						group: key.data(),
+						component,
+					})
+				}
+				Err(error) => Err(error),
+			})
+			.collect::<Result<Vec<(Key, GroupSnapshot)>, _>>()?;
+		Ok(DataSnapshot {
+			size: groups.iter().map(|(_, g)| g.size).sum(),
+			groups,
+		})
+	}
+	/// Restores every group captured by `snapshot`.
+	///
+	/// Nothing is changed if any of the groups is missing or has different
+	/// components.
+	pub fn restore(&mut self, snapshot: &DataSnapshot<Key>) -> Result<(), SnapshotError> {
+		for (key, group_snapshot) in snapshot.groups.iter() {
+			let group = self.groups.get(*key).ok_or(SnapshotError::MissingGroup)?;
+			if !group
+				.borrow()
+				.components
+				.matches(&group_snapshot.containers)
+			{
+				return Err(SnapshotError::ComponentsChanged);
+			}
+		}
+		for (key, group_snapshot) in snapshot.groups.iter() {
+			self.groups[*key].get_mut().restore(group_snapshot)?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupSummary {
+	pub len: usize,
+	pub components: Vec<ComponentInfo>,
+}
+impl GroupSummary {
+	pub fn memory(&self) -> usize {
+		self.components.iter().filter_map(|c| c.memory).sum()
+	}
+	pub fn diverging(&self) -> impl Iterator<Item = &ComponentInfo> {
+		self.components.iter().filter(|c| c.diverges(self.len))
+	}
+}
+impl Display for GroupSummary {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		writeln!(f, "{} rows, {} B", self.len, self.memory())?;
+		for component in self.components.iter() {
+			let mark = if component.diverges(self.len) {
+				"!"
+			} else {
+				" "
+			};
+			writeln!(f, "{mark} {component}")?;
+		}
+		Ok(())
 	}
 }
 
-pub type Data<Key> = SlotMap<Key, RefCell<Group>>;
+/// Summary of every group of a [Data], for debug overlays.
+#[derive(Debug, Clone)]
+pub struct DataSummary<Key: slotmap::Key> {
+	pub groups: Vec<(Key, GroupSummary)>,
+}
+impl<Key: slotmap::Key> DataSummary<Key> {
+	pub fn rows(&self) -> usize {
+		self.groups.iter().map(|(_, g)| g.len).sum()
+	}
+	pub fn memory(&self) -> usize {
+		self.groups.iter().map(|(_, g)| g.memory()).sum()
+	}
+	/// Components whose containers diverge from their group's length.
+	pub fn diverging(&self) -> impl Iterator<Item = (Key, &ComponentInfo)> {
+		self.groups
+			.iter()
+			.flat_map(|(key, g)| g.diverging().map(|c| (*key, c)))
+	}
+}
+impl<Key: slotmap::Key> Display for DataSummary<Key> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		writeln!(
+			f,
+			"{} groups, {} rows, {} B",
+			self.groups.len(),
+			self.rows(),
+			self.memory()
+		)?;
+		for (key, group) in self.groups.iter() {
+			write!(f, "{key:?}: {group}")?;
+		}
+		Ok(())
+	}
+}
@@ -1,8 +1,21 @@
+// Lets derived code refer to `::ure_data` from inside this crate too.
+extern crate self as ure_data;
+
+pub mod cell;
+pub mod commands;
 pub mod components;
 pub mod containers;
 pub mod glob;
 pub mod group;
 pub mod method;
+#[cfg(feature = "reflect")]
+pub mod reflect;
 pub mod resource;
+pub mod schedule;
+#[cfg(feature = "scripting")]
+pub mod scripting;
 pub mod signals;
+pub mod snapshot;
+pub mod state;
+pub mod timer;
 mod util;
@@ -1,8 +1,10 @@
 use std::error::Error;
 
 use crate::{
+	cell::MaybeSync,
 	components::{ComponentDependency, ComponentId},
 	glob::GlobuleRef,
+	group::Data,
 	util::all_the_tuples,
 };
 
@@ -76,5 +78,81 @@ impl<$($T,)* Args, Return, F: FnOnce($($T,)* Args) -> Return> MethodTrait<($($T,
 
 all_the_tuples!(impl_method);
 
+#[cfg(not(feature = "sync"))]
 pub type Method<Args, Return = ()> =
 	dyn for<'a, 'b> Fn(GlobuleRef<'a, 'b>, &'b mut Args) -> Result<Return, Box<dyn Error>>;
+#[cfg(feature = "sync")]
+pub type Method<Args, Return = ()> = dyn for<'a, 'b> Fn(GlobuleRef<'a, 'b>, &'b mut Args) -> Result<Return, Box<dyn Error>>
+	+ Send
+	+ Sync;
+
+pub fn method_dependencies<T: ComponentDependency, Args, Return>(
+	_: &impl MethodTrait<T, Args, Return>,
+) -> Vec<ComponentId> {
+	T::dependencies()
+}
+
+/// A boxed method and the components it needs, for running over many groups.
+///
+/// Build one with [group_method!](crate::group_method).
+pub struct GroupMethod<Args = ()> {
+	dependencies: Vec<ComponentId>,
+	method: Box<Method<Args>>,
+}
+impl<Args> GroupMethod<Args> {
+	pub fn new(
+		dependencies: Vec<ComponentId>,
+		method: impl for<'a, 'b> Fn(GlobuleRef<'a, 'b>, &'b mut Args) -> Result<(), Box<dyn Error>>
+		+ MaybeSync
+		+ 'static,
+	) -> Self {
+		Self {
+			dependencies,
+			method: Box::new(method),
+		}
+	}
+	pub fn dependencies(&self) -> &[ComponentId] {
+		&self.dependencies
+	}
+	pub fn call(&self, glob: GlobuleRef<'_, '_>, args: &mut Args) -> Result<(), Box<dyn Error>> {
+		(self.method)(glob, args)
+	}
+	/// Calls the method on every group of `data` that has its dependencies.
+	pub fn call_data<Key: slotmap::Key>(
+		&self,
+		data: &Data<Key>,
+		args: &mut Args,
+	) -> Result<(), Box<dyn Error>> {
+		for (key, group) in data.iter() {
+			let group = group.borrow();
+			if group
+				.are_depencencies_satisfied(&self.dependencies)
+				.is_err()
+			{
+				continue;
+			}
+			self.call(
+				GlobuleRef::from_group(&group)
+					.with_resources(data.resources())
+					.with_key(key),
+				args,
+			)?;
+		}
+		Ok(())
+	}
+}
+
+/// Wraps a method in a [GroupMethod], inferring its dependencies.
+///
+/// ```ignore
+/// let method: GroupMethod = group_method!(update_instances_2d);
+/// ```
+#[macro_export]
+macro_rules! group_method {
+	($method:expr) => {
+		$crate::method::GroupMethod::new(
+			$crate::method::method_dependencies(&$method),
+			|glob, args| glob.call_method($method, args),
+		)
+	};
+}
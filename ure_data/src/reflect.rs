@@ -0,0 +1,492 @@
+use std::{
+	any::{Any, type_name},
+	borrow::Cow,
+	error::Error,
+	fmt::Display,
+};
+
+use crate::{
+	cell::MaybeSync,
+	components::ComponentId,
+	containers::{Container, OneOrMany, SparseMap, SparseSet},
+};
+
+/// A dynamically typed value read from or written to a component.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	None,
+	Bool(bool),
+	Int(i64),
+	Float(f64),
+	String(String),
+	List(Vec<Value>),
+	Struct(Vec<(Cow<'static, str>, Value)>),
+}
+impl Value {
+	/// A struct field by name, or a list item by index.
+	pub fn field(&self, name: &str) -> Option<&Value> {
+		match self {
+			Self::Struct(fields) => fields.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+			Self::List(items) => items.get(name.parse::<usize>().ok()?),
+			_ => None,
+		}
+	}
+	pub fn field_mut(&mut self, name: &str) -> Option<&mut Value> {
+		match self {
+			Self::Struct(fields) => fields.iter_mut().find(|(n, _)| n == name).map(|(_, v)| v),
+			Self::List(items) => items.get_mut(name.parse::<usize>().ok()?),
+			_ => None,
+		}
+	}
+	/// Follows a `.` separated path of fields, e.g. `translation.x`. The
+	/// empty path is the value itself.
+	pub fn path(&self, path: &str) -> Option<&Value> {
+		path.split('.')
+			.filter(|name| !name.is_empty())
+			.try_fold(self, |value, name| value.field(name))
+	}
+	pub fn path_mut(&mut self, path: &str) -> Option<&mut Value> {
+		path.split('.')
+			.filter(|name| !name.is_empty())
+			.try_fold(self, |value, name| value.field_mut(name))
+	}
+	pub fn as_bool(&self) -> Option<bool> {
+		match self {
+			Self::Bool(b) => Some(*b),
+			_ => None,
+		}
+	}
+	pub fn as_int(&self) -> Option<i64> {
+		match self {
+			Self::Int(i) => Some(*i),
+			_ => None,
+		}
+	}
+	/// Ints are converted.
+	pub fn as_float(&self) -> Option<f64> {
+		match self {
+			Self::Float(f) => Some(*f),
+			Self::Int(i) => Some(*i as f64),
+			_ => None,
+		}
+	}
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			Self::String(s) => Some(s),
+			_ => None,
+		}
+	}
+}
+impl Display for Value {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::None => write!(f, "none"),
+			Self::Bool(b) => write!(f, "{b}"),
+			Self::Int(i) => write!(f, "{i}"),
+			Self::Float(x) => write!(f, "{x}"),
+			Self::String(s) => write!(f, "{s:?}"),
+			Self::List(items) => {
+				write!(f, "[")?;
+				for (i, item) in items.iter().enumerate() {
+					if i > 0 {
+						write!(f, ", ")?;
+					}
+					write!(f, "{item}")?;
+				}
+				write!(f, "]")
+			}
+			Self::Struct(fields) => {
+				write!(f, "{{")?;
+				for (i, (name, value)) in fields.iter().enumerate() {
+					if i > 0 {
+						write!(f, ",")?;
+					}
+					write!(f, " {name}: {value}")?;
+				}
+				write!(f, " }}")
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub enum ReflectError {
+	Type { expected: &'static str },
+	UnknownField(String),
+	MissingRow(usize),
+	NotReflect(ComponentId),
+}
+impl Display for ReflectError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Type { expected } => write!(f, "Expected a value of type {expected}."),
+			Self::UnknownField(name) => write!(f, "No field named {name}."),
+			Self::MissingRow(row) => write!(f, "No value at row {row}."),
+			Self::NotReflect(id) => write!(f, "Component {id:?} does not have reflection enabled."),
+		}
+	}
+}
+impl Error for ReflectError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldInfo {
+	pub name: &'static str,
+	pub type_name: &'static str,
+}
+
+/// A type whose values can be read and written as [Value]s.
+pub trait Reflect: 'static {
+	/// Names and types of the fields, empty for values without fields.
+	fn fields() -> Vec<FieldInfo>
+	where
+		Self: Sized,
+	{
+		Vec::new()
+	}
+	fn to_value(&self) -> Value;
+	/// Fields missing from a [Value::Struct] are left unchanged.
+	fn set_value(&mut self, value: &Value) -> Result<(), ReflectError>;
+}
+
+/// Implements [Reflect] for a struct with public fields.
+///
+/// ```ignore
+/// reflect_struct!(Vec2 { x: f32, y: f32 });
+/// ```
+#[macro_export]
+macro_rules! reflect_struct {
+	($ty:ty { $($field:ident: $field_ty:ty),* $(,)? }) => {
+impl $crate::reflect::Reflect for $ty {
+	fn fields() -> Vec<$crate::reflect::FieldInfo> {
+		vec![$($crate::reflect::FieldInfo {
+			name: stringify!($field),
+			type_name: std::any::type_name::<$field_ty>(),
+		}),*]
+	}
+	fn to_value(&self) -> $crate::reflect::Value {
+		$crate::reflect::Value::Struct(vec![$((
+			std::borrow::Cow::Borrowed(stringify!($field)),
+			$crate::reflect::Reflect::to_value(&self.$field),
+		)),*])
+	}
+	fn set_value(&mut self, value: &$crate::reflect::Value) -> Result<(), $crate::reflect::ReflectError> {
+		let $crate::reflect::Value::Struct(fields) = value else {
+			return Err($crate::reflect::ReflectError::Type {
+				expected: std::any::type_name::<$ty>(),
+			});
+		};
+		for (name, value) in fields {
+			match name.as_ref() {
+				$(stringify!($field) => $crate::reflect::Reflect::set_value(&mut self.$field, value)?,)*
+				_ => return Err($crate::reflect::ReflectError::UnknownField(name.to_string())),
+			}
+		}
+		Ok(())
+	}
+}
+	};
+}
+
+macro_rules! reflect_int {
+	($($ty:ty),*) => {$(
+impl Reflect for $ty {
+	fn to_value(&self) -> Value {
+		Value::Int(i64::try_from(*self).unwrap_or(i64::MAX))
+	}
+	fn set_value(&mut self, value: &Value) -> Result<(), ReflectError> {
+		*self = value
+			.as_int()
+			.and_then(|i| i.try_into().ok())
+			.ok_or(ReflectError::Type { expected: type_name::<$ty>() })?;
+		Ok(())
+	}
+}
+	)*};
+}
+reflect_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! reflect_float {
+	($($ty:ty),*) => {$(
+impl Reflect for $ty {
+	fn to_value(&self) -> Value {
+		Value::Float(*self as f64)
+	}
+	fn set_value(&mut self, value: &Value) -> Result<(), ReflectError> {
+		*self = value
+			.as_float()
+			.ok_or(ReflectError::Type { expected: type_name::<$ty>() })? as $ty;
+		Ok(())
+	}
+}
+	)*};
+}
+reflect_float!(f32, f64);
+
+impl Reflect for bool {
+	fn to_value(&self) -> Value {
+		Value::Bool(*self)
+	}
+	fn set_value(&mut self, value: &Value) -> Result<(), ReflectError> {
+		*self = value
+			.as_bool()
+			.ok_or(ReflectError::Type { expected: "bool" })?;
+		Ok(())
+	}
+}
+impl Reflect for String {
+	fn to_value(&self) -> Value {
+		Value::String(self.clone())
+	}
+	fn set_value(&mut self, value: &Value) -> Result<(), ReflectError> {
+		let s = value
+			.as_str()
+			.ok_or(ReflectError::Type { expected: "String" })?;
+		self.clear();
+		self.push_str(s);
+		Ok(())
+	}
+}
+impl<T: Reflect + Default> Reflect for Option<T> {
+	fn to_value(&self) -> Value {
+		match self {
+			Some(value) => value.to_value(),
+			None => Value::None,
+		}
+	}
+	fn set_value(&mut self, value: &Value) -> Result<(), ReflectError> {
+		match value {
+			Value::None => *self = None,
+			value => self.get_or_insert_default().set_value(value)?,
+		}
+		Ok(())
+	}
+}
+impl<T: Reflect, const N: usize> Reflect for [T; N] {
+	fn to_value(&self) -> Value {
+		Value::List(self.iter().map(Reflect::to_value).collect())
+	}
+	fn set_value(&mut self, value: &Value) -> Result<(), ReflectError> {
+		let Value::List(items) = value else {
+			return Err(ReflectError::Type {
+				expected: type_name::<Self>(),
+			});
+		};
+		if items.len() != N {
+			return Err(ReflectError::Type {
+				expected: type_name::<Self>(),
+			});
+		}
+		for (item, value) in self.iter_mut().zip(items) {
+			item.set_value(value)?;
+		}
+		Ok(())
+	}
+}
+
+mod glam_impls {
+	use glam::{
+		Affine2, Affine3A, IVec2, IVec3, Mat2, Mat3, Mat3A, Mat4, Quat, UVec2, UVec3, Vec2, Vec3,
+		Vec3A, Vec4,
+	};
+
+	reflect_struct!(Vec2 { x: f32, y: f32 });
+	reflect_struct!(Vec3 {
+		x: f32,
+		y: f32,
+		z: f32
+	});
+	reflect_struct!(Vec3A {
+		x: f32,
+		y: f32,
+		z: f32
+	});
+	reflect_struct!(Vec4 {
+		x: f32,
+		y: f32,
+		z: f32,
+		w: f32
+	});
+	reflect_struct!(IVec2 { x: i32, y: i32 });
+	reflect_struct!(IVec3 {
+		x: i32,
+		y: i32,
+		z: i32
+	});
+	reflect_struct!(UVec2 { x: u32, y: u32 });
+	reflect_struct!(UVec3 {
+		x: u32,
+		y: u32,
+		z: u32
+	});
+	reflect_struct!(Quat {
+		x: f32,
+		y: f32,
+		z: f32,
+		w: f32
+	});
+	reflect_struct!(Mat2 {
+		x_axis: Vec2,
+		y_axis: Vec2
+	});
+	reflect_struct!(Mat3 {
+		x_axis: Vec3,
+		y_axis: Vec3,
+		z_axis: Vec3
+	});
+	reflect_struct!(Mat3A {
+		x_axis: Vec3A,
+		y_axis: Vec3A,
+		z_axis: Vec3A
+	});
+	reflect_struct!(Mat4 {
+		x_axis: Vec4,
+		y_axis: Vec4,
+		z_axis: Vec4,
+		w_axis: Vec4
+	});
+	reflect_struct!(Affine2 {
+		matrix2: Mat2,
+		translation: Vec2
+	});
+	reflect_struct!(Affine3A {
+		matrix3: Mat3A,
+		translation: Vec3A
+	});
+}
+
+mod color_impls {
+	use std::borrow::Cow;
+
+	use color::{AlphaColor, Rgba8, Srgb};
+
+	use super::{FieldInfo, Reflect, ReflectError, Value};
+
+	reflect_struct!(Rgba8 {
+		r: u8,
+		g: u8,
+		b: u8,
+		a: u8
+	});
+
+	const CHANNELS: [&str; 4] = ["r", "g", "b", "a"];
+	impl Reflect for AlphaColor<Srgb> {
+		fn fields() -> Vec<FieldInfo> {
+			CHANNELS
+				.iter()
+				.map(|&name| FieldInfo {
+					name,
+					type_name: "f32",
+				})
+				.collect()
+		}
+		fn to_value(&self) -> Value {
+			Value::Struct(
+				CHANNELS
+					.iter()
+					.zip(self.components)
+					.map(|(&name, c)| (Cow::Borrowed(name), c.to_value()))
+					.collect(),
+			)
+		}
+		fn set_value(&mut self, value: &Value) -> Result<(), ReflectError> {
+			let Value::Struct(fields) = value else {
+				return Err(ReflectError::Type { expected: "Srgba" });
+			};
+			for (name, value) in fields {
+				let channel = CHANNELS
+					.iter()
+					.position(|c| c == name)
+					.ok_or_else(|| ReflectError::UnknownField(name.to_string()))?;
+				self.components[channel].set_value(value)?;
+			}
+			Ok(())
+		}
+	}
+}
+
+/// A [Container] whose rows are [Reflect] values.
+pub trait ReflectContainer: Container {
+	type Item: Reflect;
+
+	fn reflect_row(&self, row: usize) -> Option<&Self::Item>;
+	fn reflect_row_mut(&mut self, row: usize) -> Option<&mut Self::Item>;
+}
+impl<T: Reflect + MaybeSync> ReflectContainer for Vec<T> {
+	type Item = T;
+
+	fn reflect_row(&self, row: usize) -> Option<&T> {
+		self.get(row)
+	}
+	fn reflect_row_mut(&mut self, row: usize) -> Option<&mut T> {
+		self.get_mut(row)
+	}
+}
+impl<T: Reflect + MaybeSync> ReflectContainer for SparseSet<T> {
+	type Item = T;
+
+	fn reflect_row(&self, row: usize) -> Option<&T> {
+		self.get(row)
+	}
+	fn reflect_row_mut(&mut self, row: usize) -> Option<&mut T> {
+		self.get_mut(row)
+	}
+}
+impl<T: Reflect + MaybeSync> ReflectContainer for SparseMap<T> {
+	type Item = T;
+
+	fn reflect_row(&self, row: usize) -> Option<&T> {
+		self.get(row)
+	}
+	fn reflect_row_mut(&mut self, row: usize) -> Option<&mut T> {
+		self.get_mut(row)
+	}
+}
+/// A single value is shared, writing to any row writes to all of them.
+impl<T: Reflect + MaybeSync> ReflectContainer for OneOrMany<T> {
+	type Item = T;
+
+	fn reflect_row(&self, row: usize) -> Option<&T> {
+		match self {
+			OneOrMany::One(item) => Some(item),
+			OneOrMany::Many(items) => items.get(row),
+			OneOrMany::None => None,
+		}
+	}
+	fn reflect_row_mut(&mut self, row: usize) -> Option<&mut T> {
+		match self {
+			OneOrMany::One(item) => Some(item),
+			OneOrMany::Many(items) => items.get_mut(row),
+			OneOrMany::None => None,
+		}
+	}
+}
+
+/// Reads and writes rows of a type-erased container.
+///
+/// Use [ReflectVTable::of] for a [ReflectContainer], or fill in the functions
+/// by hand for containers of types that cannot implement [Reflect].
+#[derive(Debug, Clone, Copy)]
+pub struct ReflectVTable {
+	pub type_name: &'static str,
+	pub fields: fn() -> Vec<FieldInfo>,
+	/// [None] if the container is not the expected type or has no value at
+	/// the row.
+	pub get: fn(&dyn Any, usize) -> Option<Value>,
+	pub set: fn(&mut dyn Any, usize, &Value) -> Result<(), ReflectError>,
+}
+impl ReflectVTable {
+	pub fn of<C: ReflectContainer>() -> Self {
+		Self {
+			type_name: type_name::<C::Item>(),
+			fields: C::Item::fields,
+			get: |container, row| Some(container.downcast_ref::<C>()?.reflect_row(row)?.to_value()),
+			set: |container, row, value| {
+				container
+					.downcast_mut::<C>()
+					.and_then(|c| c.reflect_row_mut(row))
+					.ok_or(ReflectError::MissingRow(row))?
+					.set_value(value)
+			},
+		}
+	}
+}
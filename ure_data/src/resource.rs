@@ -1,7 +1,15 @@
-use std::{sync::Arc, sync::Weak};
+use std::{
+	any::TypeId,
+	collections::HashMap,
+	error::Error,
+	fmt::Display,
+	sync::{Arc, Weak},
+};
 
 use parking_lot::Mutex;
 
+use crate::cell::{self, AnyBox, MaybeSync, RefCell};
+
 pub struct Resource<T, F = fn() -> T> {
 	weak: Mutex<Weak<T>>,
 	f: F,
@@ -23,3 +31,50 @@ impl<T, F: Fn() -> T> Resource<T, F> {
 		arc
 	}
 }
+
+/// Singletons stored beside rows, one per type.
+#[derive(Debug, Default)]
+pub struct Resources {
+	inner: HashMap<TypeId, RefCell<AnyBox>>,
+}
+impl Resources {
+	pub fn insert<T: MaybeSync + 'static>(&mut self, value: T) -> Option<T> {
+		let old = self
+			.inner
+			.insert(TypeId::of::<T>(), RefCell::new(Box::new(value)))?;
+		Some(*old.into_inner().downcast().unwrap())
+	}
+	pub fn get<T: 'static>(&self) -> Option<cell::Ref<'_, T>> {
+		Some(cell::Ref::map(
+			self.inner.get(&TypeId::of::<T>())?.borrow(),
+			|r| r.downcast_ref::<T>().unwrap(),
+		))
+	}
+	pub fn get_mut<T: 'static>(&self) -> Option<cell::RefMut<'_, T>> {
+		Some(cell::RefMut::map(
+			self.inner.get(&TypeId::of::<T>())?.borrow_mut(),
+			|r| r.downcast_mut::<T>().unwrap(),
+		))
+	}
+	pub fn remove<T: 'static>(&mut self) -> Option<T> {
+		let old = self.inner.remove(&TypeId::of::<T>())?;
+		Some(*old.into_inner().downcast().unwrap())
+	}
+	pub fn contains<T: 'static>(&self) -> bool {
+		self.inner.contains_key(&TypeId::of::<T>())
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MissingResource(pub &'static str);
+impl MissingResource {
+	pub fn of<T>() -> Self {
+		Self(std::any::type_name::<T>())
+	}
+}
+impl Display for MissingResource {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Missing resource {}.", self.0)
+	}
+}
+impl Error for MissingResource {}
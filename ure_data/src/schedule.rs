@@ -0,0 +1,378 @@
+use std::{error::Error, fmt::Display, time::Duration};
+
+use crate::{group::Data, method::GroupMethod};
+
+pub type System<Key> = dyn FnMut(&Data<Key>) -> Result<(), Box<dyn Error>>;
+pub type Condition<Key> = dyn Fn(&Data<Key>) -> bool;
+
+#[derive(Debug)]
+pub enum ScheduleError {
+	MissingStage(&'static str),
+	UnknownLabel {
+		system: &'static str,
+		label: &'static str,
+	},
+	Cycle(&'static str),
+	System {
+		system: &'static str,
+		error: Box<dyn Error>,
+	},
+}
+impl Display for ScheduleError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::MissingStage(stage) => write!(f, "No stage named {stage}."),
+			Self::UnknownLabel { system, label } => {
+				write!(
+					f,
+					"System {system} is ordered against unknown label {label}."
+				)
+			}
+			Self::Cycle(system) => write!(f, "System {system} is part of an ordering cycle."),
+			Self::System { system, error } => write!(f, "System {system} failed: {error}"),
+		}
+	}
+}
+impl Error for ScheduleError {}
+
+struct Entry<Key: slotmap::Key> {
+	label: &'static str,
+	system: Box<System<Key>>,
+	before: Vec<&'static str>,
+	after: Vec<&'static str>,
+	conditions: Vec<Box<Condition<Key>>>,
+}
+
+/// Splits passing time into steps of a fixed length, carrying the remainder
+/// over to the next [FixedTimestep::advance].
+#[derive(Debug, Clone, Copy)]
+pub struct FixedTimestep {
+	step: Duration,
+	max_steps: u32,
+	accumulator: Duration,
+}
+impl FixedTimestep {
+	/// Runs at most `max_steps` steps of `step` per advance.
+	pub fn new(step: Duration, max_steps: u32) -> Self {
+		assert!(!step.is_zero(), "Fixed step is zero.");
+		Self {
+			step,
+			max_steps,
+			accumulator: Duration::ZERO,
+		}
+	}
+	pub fn step(&self) -> Duration {
+		self.step
+	}
+	pub fn max_steps(&self) -> u32 {
+		self.max_steps
+	}
+	/// Keeps the time accumulated so far.
+	pub fn set_step(&mut self, step: Duration, max_steps: u32) {
+		assert!(!step.is_zero(), "Fixed step is zero.");
+		self.step = step;
+		self.max_steps = max_steps;
+	}
+	/// Adds `delta`, returning how many steps to run for it.
+	pub fn advance(&mut self, delta: Duration) -> u32 {
+		self.accumulator += delta;
+		let steps = self.accumulator.as_nanos() / self.step.as_nanos();
+		if steps > self.max_steps as u128 {
+			// Too far behind, drop the backlog rather than spiral.
+			self.accumulator = Duration::ZERO;
+			self.max_steps
+		} else {
+			let steps = steps as u32;
+			self.accumulator -= self.step * steps;
+			steps
+		}
+	}
+	/// Progress towards the next step, within `0..1`.
+	pub fn alpha(&self) -> f32 {
+		self.accumulator.as_secs_f32() / self.step.as_secs_f32()
+	}
+}
+
+struct Stage<Key: slotmap::Key> {
+	name: &'static str,
+	fixed: Option<FixedTimestep>,
+	conditions: Vec<Box<Condition<Key>>>,
+	systems: Vec<Entry<Key>>,
+	order: Option<Vec<usize>>,
+}
+impl<Key: slotmap::Key> Stage<Key> {
+	fn new(name: &'static str, fixed: Option<FixedTimestep>) -> Self {
+		Self {
+			name,
+			fixed,
+			conditions: Vec::new(),
+			systems: Vec::new(),
+			order: None,
+		}
+	}
+	/// Orders systems by their constraints, ties keep insertion order.
+	fn sort(&self) -> Result<Vec<usize>, ScheduleError> {
+		let len = self.systems.len();
+		let mut edges = vec![Vec::new(); len];
+		let mut incoming = vec![0usize; len];
+		let find = |system: &'static str, label: &'static str| {
+			let found: Vec<usize> = (0..len)
+				.filter(|&i| self.systems[i].label == label)
+				.collect();
+			if found.is_empty() {
+				Err(ScheduleError::UnknownLabel { system, label })
+			} else {
+				Ok(found)
+			}
+		};
+		for (i, entry) in self.systems.iter().enumerate() {
+			for &label in entry.before.iter() {
+				for j in find(entry.label, label)? {
+					edges[i].push(j);
+					incoming[j] += 1;
+				}
+			}
+			for &label in entry.after.iter() {
+				for j in find(entry.label, label)? {
+					edges[j].push(i);
+					incoming[i] += 1;
+				}
+			}
+		}
+		let mut order = Vec::with_capacity(len);
+		let mut done = vec![false; len];
+		while order.len() < len {
+			let Some(next) = (0..len).find(|&i| !done[i] && incoming[i] == 0) else {
+				let stuck = (0..len).find(|&i| !done[i]).unwrap();
+				return Err(ScheduleError::Cycle(self.systems[stuck].label));
+			};
+			done[next] = true;
+			for &j in edges[next].iter() {
+				incoming[j] -= 1;
+			}
+			order.push(next);
+		}
+		Ok(order)
+	}
+	fn run_systems(&mut self, data: &Data<Key>) -> Result<(), ScheduleError> {
+		let order = match self.order.take() {
+			Some(order) => order,
+			None => self.sort()?,
+		};
+		let result = order.iter().try_for_each(|&i| {
+			let entry = &mut self.systems[i];
+			if !entry.conditions.iter().all(|c| c(data)) {
+				return Ok(());
+			}
+			(entry.system)(data).map_err(|error| ScheduleError::System {
+				system: entry.label,
+				error,
+			})
+		});
+		self.order = Some(order);
+		result
+	}
+	fn run(&mut self, data: &Data<Key>, delta: Duration) -> Result<(), ScheduleError> {
+		if !self.conditions.iter().all(|c| c(data)) {
+			return Ok(());
+		}
+		let Some(fixed) = self.fixed.as_mut() else {
+			return self.run_systems(data);
+		};
+		for _ in 0..fixed.advance(delta) {
+			self.run_systems(data)?;
+		}
+		Ok(())
+	}
+}
+
+/// Systems grouped into named stages, run in order against a [Data] each tick.
+///
+/// Stages run in the order they were added. Within a stage, systems run in the
+/// order they were added unless constrained with [SystemConfig::before] or
+/// [SystemConfig::after].
+pub struct Schedule<Key: slotmap::Key> {
+	stages: Vec<Stage<Key>>,
+}
+impl<Key: slotmap::Key> Default for Schedule<Key> {
+	fn default() -> Self {
+		Self { stages: Vec::new() }
+	}
+}
+impl<Key: slotmap::Key> Schedule<Key> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	pub fn add_stage(&mut self, name: &'static str) -> StageConfig<'_, Key> {
+		self.stages.push(Stage::new(name, None));
+		StageConfig {
+			stage: self.stages.last_mut().unwrap(),
+		}
+	}
+	/// Adds a stage that runs once per `step` of accumulated time, catching up
+	/// at most `max_steps` times per tick.
+	pub fn add_fixed_stage(
+		&mut self,
+		name: &'static str,
+		step: Duration,
+		max_steps: u32,
+	) -> StageConfig<'_, Key> {
+		assert!(!step.is_zero(), "Fixed stage {name} has a zero step.");
+		self.stages
+			.push(Stage::new(name, Some(FixedTimestep::new(step, max_steps))));
+		StageConfig {
+			stage: self.stages.last_mut().unwrap(),
+		}
+	}
+	pub fn stage(&mut self, name: &'static str) -> Option<StageConfig<'_, Key>> {
+		Some(StageConfig {
+			stage: self.stages.iter_mut().find(|s| s.name == name)?,
+		})
+	}
+	pub fn add_system(
+		&mut self,
+		stage: &'static str,
+		label: &'static str,
+		system: impl FnMut(&Data<Key>) -> Result<(), Box<dyn Error>> + 'static,
+	) -> Result<SystemConfig<'_, Key>, ScheduleError> {
+		let stage = self
+			.stages
+			.iter_mut()
+			.find(|s| s.name == stage)
+			.ok_or(ScheduleError::MissingStage(stage))?;
+		stage.order = None;
+		stage.systems.push(Entry {
+			label,
+			system: Box::new(system),
+			before: Vec::new(),
+			after: Vec::new(),
+			conditions: Vec::new(),
+		});
+		Ok(SystemConfig {
+			order: &mut stage.order,
+			entry: stage.systems.last_mut().unwrap(),
+		})
+	}
+	/// Adds a system calling `method` on every group that has its dependencies.
+	pub fn add_method(
+		&mut self,
+		stage: &'static str,
+		label: &'static str,
+		method: GroupMethod,
+	) -> Result<SystemConfig<'_, Key>, ScheduleError> {
+		self.add_system(stage, label, move |data| method.call_data(data, &mut ()))
+	}
+	/// Runs every stage once, fixed stages as many times as `delta` allows.
+	pub fn run(&mut self, data: &Data<Key>, delta: Duration) -> Result<(), ScheduleError> {
+		for stage in self.stages.iter_mut() {
+			stage.run(data, delta)?;
+		}
+		Ok(())
+	}
+	/// Checks every ordering constraint without running anything.
+	pub fn validate(&mut self) -> Result<(), ScheduleError> {
+		for stage in self.stages.iter_mut() {
+			if stage.order.is_none() {
+				stage.order = Some(stage.sort()?);
+			}
+		}
+		Ok(())
+	}
+}
+
+pub struct StageConfig<'a, Key: slotmap::Key> {
+	stage: &'a mut Stage<Key>,
+}
+impl<Key: slotmap::Key> StageConfig<'_, Key> {
+	/// Skips the whole stage unless `condition` holds.
+	pub fn run_if(self, condition: impl Fn(&Data<Key>) -> bool + 'static) -> Self {
+		self.stage.conditions.push(Box::new(condition));
+		self
+	}
+}
+
+pub struct SystemConfig<'a, Key: slotmap::Key> {
+	order: &'a mut Option<Vec<usize>>,
+	entry: &'a mut Entry<Key>,
+}
+impl<Key: slotmap::Key> SystemConfig<'_, Key> {
+	pub fn before(self, label: &'static str) -> Self {
+		*self.order = None;
+		self.entry.before.push(label);
+		self
+	}
+	pub fn after(self, label: &'static str) -> Self {
+		*self.order = None;
+		self.entry.after.push(label);
+		self
+	}
+	/// Skips the system unless `condition` holds.
+	pub fn run_if(self, condition: impl Fn(&Data<Key>) -> bool + 'static) -> Self {
+		self.entry.conditions.push(Box::new(condition));
+		self
+	}
+}
+
+/// Condition holding while the [Data] has a resource of type `T`.
+pub fn resource_exists<Key: slotmap::Key, T: 'static>() -> impl Fn(&Data<Key>) -> bool {
+	|data| data.resources().contains::<T>()
+}
+
+/// Condition holding while the [Data]'s resource of type `T` equals `value`.
+pub fn resource_equals<Key: slotmap::Key, T: PartialEq + 'static>(
+	value: T,
+) -> impl Fn(&Data<Key>) -> bool {
+	move |data| data.resource::<T>().is_some_and(|r| *r == value)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{cell::Cell, rc::Rc, time::Duration};
+
+	use slotmap::DefaultKey;
+
+	use super::{FixedTimestep, Schedule};
+	use crate::group::Data;
+
+	fn ms(ms: u64) -> Duration {
+		Duration::from_millis(ms)
+	}
+
+	#[test]
+	fn fixed_timestep_carries_the_remainder() {
+		let mut fixed = FixedTimestep::new(ms(10), 3);
+		assert_eq!(fixed.advance(ms(25)), 2);
+		assert!((fixed.alpha() - 0.5).abs() < 1e-6);
+		assert_eq!(fixed.advance(ms(5)), 1);
+		assert_eq!(fixed.alpha(), 0.0);
+		assert_eq!(fixed.advance(ms(35)), 3);
+		assert!((fixed.alpha() - 0.5).abs() < 1e-6);
+	}
+
+	#[test]
+	fn fixed_timestep_drops_the_backlog() {
+		let mut fixed = FixedTimestep::new(ms(10), 3);
+		assert_eq!(fixed.advance(ms(45)), 3);
+		assert_eq!(fixed.alpha(), 0.0);
+		assert_eq!(fixed.advance(ms(9)), 0);
+	}
+
+	#[test]
+	fn fixed_stage_runs_per_step() {
+		let data: Data<DefaultKey> = Data::new();
+		let runs = Rc::new(Cell::new(0));
+		let mut schedule = Schedule::new();
+		schedule.add_fixed_stage("fixed", ms(10), 4);
+		let counter = runs.clone();
+		schedule
+			.add_system("fixed", "count", move |_| {
+				counter.set(counter.get() + 1);
+				Ok(())
+			})
+			.unwrap();
+		schedule.run(&data, ms(25)).unwrap();
+		assert_eq!(runs.get(), 2);
+		schedule.run(&data, ms(100)).unwrap();
+		assert_eq!(runs.get(), 6);
+	}
+}
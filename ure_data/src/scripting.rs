@@ -0,0 +1,335 @@
+use std::{cell::Cell, error::Error, marker::PhantomData, path::Path, ptr::NonNull};
+
+use parking_lot::ReentrantMutex;
+use rhai::{AST, Array, Dynamic, Engine, EvalAltResult, FuncArgs, INT, Map, Scope};
+
+use crate::{
+	cell::{RefCell, Shared},
+	commands::CommandQueue,
+	components::{ComponentId, NewArgs},
+	glob::GlobuleRef,
+	group::{CLEAR, DELETE, Group, NEW},
+	method::GroupMethod,
+	reflect::Value,
+};
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+pub fn to_dynamic(value: Value) -> Dynamic {
+	match value {
+		Value::None => Dynamic::UNIT,
+		Value::Bool(b) => b.into(),
+		Value::Int(i) => (i as INT).into(),
+		Value::Float(f) => f.into(),
+		Value::String(s) => s.into(),
+		Value::List(items) => items.into_iter().map(to_dynamic).collect::<Array>().into(),
+		Value::Struct(fields) => fields
+			.into_iter()
+			.map(|(name, value)| (name.as_ref().into(), to_dynamic(value)))
+			.collect::<Map>()
+			.into(),
+	}
+}
+
+pub fn from_dynamic(value: &Dynamic) -> ScriptResult<Value> {
+	if value.is_unit() {
+		Ok(Value::None)
+	} else if let Ok(b) = value.as_bool() {
+		Ok(Value::Bool(b))
+	} else if let Ok(i) = value.as_int() {
+		Ok(Value::Int(i))
+	} else if let Ok(f) = value.as_float() {
+		Ok(Value::Float(f))
+	} else if value.is_string() {
+		Ok(Value::String(value.clone().into_string()?))
+	} else if let Some(items) = value.read_lock::<Array>() {
+		Ok(Value::List(
+			items
+				.iter()
+				.map(from_dynamic)
+				.collect::<ScriptResult<_>>()?,
+		))
+	} else if let Some(fields) = value.read_lock::<Map>() {
+		Ok(Value::Struct(
+			fields
+				.iter()
+				.map(|(name, value)| Ok((name.to_string().into(), from_dynamic(value)?)))
+				.collect::<ScriptResult<_>>()?,
+		))
+	} else {
+		Err(format!("Cannot convert {} to a component value.", value.type_name()).into())
+	}
+}
+
+/// The group a script is running on, only set while it runs.
+///
+/// Locked for the whole run, so scripts sharing an engine run one at a time
+/// when called from several threads.
+#[derive(Clone, Default)]
+struct Context(Shared<ReentrantMutex<Cell<Option<Entered>>>>);
+/// Points to the [GlobuleRef] given to [Context::enter], on its stack.
+#[derive(Clone, Copy)]
+struct Entered(NonNull<()>);
+// SAFETY: Only read by the thread holding the context lock, while the
+// globule it points to is borrowed by `enter` on that thread.
+unsafe impl Send for Entered {}
+impl Context {
+	fn enter<R>(&self, glob: GlobuleRef<'_, '_>, f: impl FnOnce() -> R) -> R {
+		struct Reset<'a>(&'a Cell<Option<Entered>>, Option<Entered>);
+		impl Drop for Reset<'_> {
+			fn drop(&mut self) {
+				self.0.set(self.1);
+			}
+		}
+		let entered = self.0.lock();
+		let _reset = Reset(
+			&entered,
+			entered.replace(Some(Entered(NonNull::from(&glob).cast()))),
+		);
+		f()
+	}
+	/// Runs `f` on the group, which cannot keep the globule past the call.
+	fn with<R>(
+		&self,
+		f: impl for<'a, 'b> FnOnce(GlobuleRef<'a, 'b>) -> ScriptResult<R>,
+	) -> ScriptResult<R> {
+		let entered = self.0.lock();
+		let Some(Entered(glob)) = entered.get() else {
+			return Err("Not running on a group.".into());
+		};
+		// SAFETY: Set by `enter`, which outlives this call as the lock is
+		// held, and reset before its globule goes out of scope.
+		let glob = unsafe { *glob.cast::<GlobuleRef<'_, '_>>().as_ref() };
+		f(glob)
+	}
+}
+
+fn component_id(group: &Group, name: &str) -> ScriptResult<ComponentId> {
+	group
+		.reflect_id(name)
+		.ok_or_else(|| format!("No reflected component named {name}.").into())
+}
+
+fn row(row: INT) -> ScriptResult<usize> {
+	usize::try_from(row).map_err(|_| format!("Invalid row {row}.").into())
+}
+
+fn queue<Key: slotmap::Key + 'static>(
+	context: &Context,
+	f: impl FnOnce(&mut CommandQueue<Key>, Key),
+) -> ScriptResult<()> {
+	context.with(|glob| {
+		let key = glob
+			.key::<Key>()
+			.ok_or("Group key unknown, cannot queue commands.")?;
+		let mut queue = glob
+			.resource_mut::<CommandQueue<Key>>()
+			.ok_or("Missing resource CommandQueue.")?;
+		f(&mut queue, key);
+		Ok(())
+	})
+}
+
+fn register<Key: slotmap::Key + 'static>(engine: &mut Engine, context: &Context) {
+	let c = context.clone();
+	engine.register_fn("len", move || -> ScriptResult<INT> {
+		c.with(|glob| Ok(glob.group().len() as INT))
+	});
+	let c = context.clone();
+	engine.register_fn(
+		"get",
+		move |component: &str, r: INT| -> ScriptResult<Dynamic> {
+			c.with(|glob| {
+				let group = glob.group();
+				let value = group
+					.reflect_get(component_id(group, component)?, row(r)?, "")
+					.map_err(|e| e.to_string())?;
+				Ok(to_dynamic(value))
+			})
+		},
+	);
+	let c = context.clone();
+	engine.register_fn(
+		"get",
+		move |component: &str, r: INT, path: &str| -> ScriptResult<Dynamic> {
+			c.with(|glob| {
+				let group = glob.group();
+				let value = group
+					.reflect_get(component_id(group, component)?, row(r)?, path)
+					.map_err(|e| e.to_string())?;
+				Ok(to_dynamic(value))
+			})
+		},
+	);
+	let c = context.clone();
+	engine.register_fn(
+		"set",
+		move |component: &str, r: INT, path: &str, value: Dynamic| -> ScriptResult<()> {
+			c.with(|glob| {
+				let group = glob.group();
+				group
+					.reflect_set(
+						component_id(group, component)?,
+						row(r)?,
+						path,
+						from_dynamic(&value)?,
+					)
+					.map_err(|e| e.to_string().into())
+			})
+		},
+	);
+	let c = context.clone();
+	engine.register_fn("new_rows", move |count: INT| -> ScriptResult<()> {
+		let count = row(count)?;
+		queue::<Key>(&c, |queue, key| queue.new_rows(key, NewArgs::new(count)))
+	});
+	let c = context.clone();
+	engine.register_fn(
+		"new_rows",
+		move |count: INT, values: Map| -> ScriptResult<()> {
+			let count = row(count)?;
+			let values = values
+				.iter()
+				.map(|(name, value)| Ok((name.to_string(), from_dynamic(value)?)))
+				.collect::<ScriptResult<Vec<_>>>()?;
+			queue::<Key>(&c, |queue, key| {
+				queue.modify(key, move |group| {
+					let first = group.len();
+					group.new(count).done()?;
+					for (name, value) in values {
+						let id = component_id(group, &name).map_err(|e| e.to_string())?;
+						for row in first..first + count {
+							group.reflect_set(id, row, "", value.clone())?;
+						}
+					}
+					Ok(())
+				})
+			})
+		},
+	);
+	let c = context.clone();
+	engine.register_fn("delete", move |r: INT| -> ScriptResult<()> {
+		let r = row(r)?;
+		queue::<Key>(&c, |queue, key| queue.delete(key, &[r]))
+	});
+}
+
+/// Rhai scripts run as methods on groups.
+///
+/// Scripts read and write reflected components by name:
+///
+/// ```ignore
+/// fn update() {
+///     for i in 0..len() {
+///         let x = get("Transforms2D", i, "translation.x");
+///         set("Transforms2D", i, "translation.x", x + 1.0);
+///     }
+/// }
+/// ```
+///
+/// `new_rows(count)`, `new_rows(count, #{ Component: value })` and `delete(row)`
+/// are queued into the [CommandQueue] resource.
+pub struct Scripts<Key: slotmap::Key> {
+	engine: Shared<Engine>,
+	ast: Shared<RefCell<AST>>,
+	context: Context,
+	_marker: PhantomData<Key>,
+}
+impl<Key: slotmap::Key + 'static> Default for Scripts<Key> {
+	fn default() -> Self {
+		let context = Context::default();
+		let mut engine = Engine::new();
+		register::<Key>(&mut engine, &context);
+		Self {
+			engine: Shared::new(engine),
+			ast: Default::default(),
+			context,
+			_marker: PhantomData,
+		}
+	}
+}
+impl<Key: slotmap::Key + 'static> Scripts<Key> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	pub fn engine(&self) -> &Engine {
+		&self.engine
+	}
+	/// For registering more functions, [None] once methods have been made.
+	pub fn engine_mut(&mut self) -> Option<&mut Engine> {
+		Shared::get_mut(&mut self.engine)
+	}
+	/// Compiles `source`, replacing the loaded script. Methods and signals
+	/// already made from the scripts call the new functions.
+	pub fn load(&self, source: &str) -> Result<(), Box<dyn Error>> {
+		*self.ast.borrow_mut() = self.engine.compile(source)?;
+		Ok(())
+	}
+	pub fn load_file(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+		*self.ast.borrow_mut() = self.engine.compile_file(path.as_ref().into())?;
+		Ok(())
+	}
+	/// Calls the script function `name` on a group.
+	pub fn call(
+		&self,
+		glob: GlobuleRef<'_, '_>,
+		name: &str,
+		args: impl FuncArgs,
+	) -> Result<Dynamic, Box<dyn Error>> {
+		call(&self.engine, &self.ast, &self.context, glob, name, args)
+	}
+	/// A method calling the script function `name` without arguments.
+	pub fn method(&self, name: &str) -> GroupMethod {
+		let (engine, ast, context) = self.shared();
+		let name = name.to_string();
+		GroupMethod::new(Vec::new(), move |glob, _: &mut ()| {
+			call(&engine, &ast, &context, glob, &name, ()).map(|_| ())
+		})
+	}
+	/// Calls the script function `name(first, count)` when rows are created.
+	pub fn connect_new(&self, group: &mut Group, name: &str) {
+		let (engine, ast, context) = self.shared();
+		let name = name.to_string();
+		group.connect_signal(&NEW, move |glob, args| {
+			let first = glob.group().len() as INT;
+			let count = args.len() as INT;
+			call(&engine, &ast, &context, glob, &name, (first, count)).map(|_| ())
+		});
+	}
+	/// Calls the script function `name(rows)` before rows are deleted.
+	pub fn connect_delete(&self, group: &mut Group, name: &str) {
+		let (engine, ast, context) = self.shared();
+		let name = name.to_string();
+		group.connect_signal(&DELETE, move |glob, indices| {
+			let rows: Array = indices.iter().map(|&i| (i as INT).into()).collect();
+			call(&engine, &ast, &context, glob, &name, (rows,)).map(|_| ())
+		});
+	}
+	/// Calls the script function `name()` before every row is cleared.
+	pub fn connect_clear(&self, group: &mut Group, name: &str) {
+		let (engine, ast, context) = self.shared();
+		let name = name.to_string();
+		group.connect_signal(&CLEAR, move |glob, _| {
+			call(&engine, &ast, &context, glob, &name, ()).map(|_| ())
+		});
+	}
+	fn shared(&self) -> (Shared<Engine>, Shared<RefCell<AST>>, Context) {
+		(self.engine.clone(), self.ast.clone(), self.context.clone())
+	}
+}
+
+fn call(
+	engine: &Engine,
+	ast: &RefCell<AST>,
+	context: &Context,
+	glob: GlobuleRef<'_, '_>,
+	name: &str,
+	args: impl FuncArgs,
+) -> Result<Dynamic, Box<dyn Error>> {
+	let ast = ast.borrow();
+	context
+		.enter(glob, || {
+			engine.call_fn::<Dynamic>(&mut Scope::new(), &ast, name, args)
+		})
+		.map_err(|e| e.into())
+}
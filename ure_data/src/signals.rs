@@ -1,9 +1,9 @@
-use std::{collections::HashMap, error::Error, marker::PhantomData};
+use std::{collections::HashMap, error::Error, fmt::Display, marker::PhantomData};
 
 use nohash_hasher::BuildNoHashHasher;
 use slotmap::{SlotMap, new_key_type};
 
-use crate::{glob::GlobuleRef, method::Method};
+use crate::{cell::MaybeSync, glob::GlobuleRef, method::Method};
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct SignalId<Args>(u64, PhantomData<Args>);
@@ -24,15 +24,30 @@ new_key_type! {
 	pub struct ConnectionId;
 }
 
+/// Methods connected to one signal.
+///
+/// Methods are called in the order they were connected.
 #[derive(Default)]
 pub struct Signal {
 	methods: SlotMap<ConnectionId, Box<Method<()>>>,
 }
 impl Signal {
-	pub unsafe fn call<Args>(&self, glob: GlobuleRef<'_, '_>, mut args: Args) {
+	/// Calls every method, even after one fails, and returns their errors.
+	///
+	/// # Safety
+	/// `Args` must be the type the methods were connected with.
+	pub unsafe fn call<Args>(&self, glob: GlobuleRef<'_, '_>, args: &mut Args) -> Result<(), SignalError> {
+		let mut errors = Vec::new();
 		for method in self.methods.values() {
 			let f = unsafe { std::mem::transmute::<&Method<()>, &Method<Args>>(method) };
-			(f)(glob.clone(), &mut args);
+			if let Err(error) = (f)(glob, args) {
+				errors.push(error);
+			}
+		}
+		if errors.is_empty() {
+			Ok(())
+		} else {
+			Err(SignalError { errors })
 		}
 	}
 	pub unsafe fn connect(&mut self, method: Box<Method<()>>) -> ConnectionId {
@@ -54,7 +69,9 @@ pub struct Signals {
 impl Signals {
 	pub fn connect<
 		Args,
-		M: for<'a, 'b, 'c> Fn(GlobuleRef<'a, 'b>, &'c mut Args) -> Result<(), Box<dyn Error>> + 'static,
+		M: for<'a, 'b, 'c> Fn(GlobuleRef<'a, 'b>, &'c mut Args) -> Result<(), Box<dyn Error>>
+			+ MaybeSync
+			+ 'static,
 	>(
 		&mut self,
 		signal_id: &SignalId<Args>,
@@ -70,10 +87,32 @@ impl Signals {
 		};
 		unsafe { signal.connect(method) };
 	}
-	pub fn call<Args>(&self, signal_id: &SignalId<Args>, glob: GlobuleRef<'_, '_>, args: Args) {
+	/// Calls every method connected to the signal and collects their errors.
+	pub fn call<Args>(
+		&self,
+		signal_id: &SignalId<Args>,
+		glob: GlobuleRef<'_, '_>,
+		args: &mut Args,
+	) -> Result<(), SignalError> {
 		let Some(signal) = self.inner.get(&signal_id.0) else {
-			return;
+			return Ok(());
 		};
-		unsafe { signal.call(glob, args) };
+		unsafe { signal.call(glob, args) }
+	}
+}
+
+/// Errors of the methods connected to a signal, in the order they ran.
+#[derive(Debug)]
+pub struct SignalError {
+	pub errors: Vec<Box<dyn Error>>,
+}
+impl Display for SignalError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Signal methods failed:")?;
+		for error in &self.errors {
+			write!(f, " {error}")?;
+		}
+		Ok(())
 	}
 }
+impl Error for SignalError {}
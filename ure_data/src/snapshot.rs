@@ -0,0 +1,162 @@
+use std::{
+	collections::{HashMap, VecDeque},
+	error::Error,
+	fmt::Display,
+};
+
+use nohash_hasher::BuildNoHashHasher;
+use slotmap::KeyData;
+
+use crate::{cell::AnyShared, components::ComponentId};
+
+/// A copy of one container, shared by every snapshot taken while the
+/// container was unchanged.
+#[derive(Clone)]
+pub(crate) struct ContainerSnapshot {
+	pub(crate) value: AnyShared,
+	pub(crate) size: usize,
+}
+
+pub(crate) type ContainerSnapshots =
+	HashMap<ComponentId, ContainerSnapshot, BuildNoHashHasher<ComponentId>>;
+
+/// Length and containers of a [Group](crate::group::Group).
+pub struct GroupSnapshot {
+	pub(crate) len: usize,
+	pub(crate) containers: ContainerSnapshots,
+	pub(crate) size: usize,
+}
+impl GroupSnapshot {
+	pub fn len(&self) -> usize {
+		self.len
+	}
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+	/// Estimated bytes copied when the snapshot was taken.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+}
+
+/// Every group of a [Data](crate::group::Data).
+///
+/// Resources and the set of groups are not captured either, so inserting or
+/// removing groups is not undone by a restore.
+pub struct DataSnapshot<Key: slotmap::Key> {
+	pub(crate) groups: Vec<(Key, GroupSnapshot)>,
+	pub(crate) size: usize,
+}
+impl<Key: slotmap::Key> DataSnapshot<Key> {
+	pub fn group(&self, key: Key) -> Option<&GroupSnapshot> {
+		self.groups.iter().find(|(k, _)| *k == key).map(|(_, g)| g)
+	}
+	pub fn keys(&self) -> impl Iterator<Item = Key> {
+		self.groups.iter().map(|(key, _)| *key)
+	}
+	/// Estimated bytes copied when the snapshot was taken. Containers shared
+	/// with an earlier snapshot cost nothing.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum SnapshotError {
+	/// The component's container does not have snapshots enabled.
+	NotSnapshot(ComponentId),
+	/// A component of a group in the data does not have snapshots enabled.
+	GroupNotSnapshot {
+		group: KeyData,
+		component: ComponentId,
+	},
+	/// A group in the snapshot is no longer in the data.
+	MissingGroup,
+	/// Components were added to or removed from a group since the snapshot.
+	ComponentsChanged,
+}
+impl Display for SnapshotError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::NotSnapshot(id) => write!(f, "Component {id:?} does not have snapshots enabled."),
+			Self::GroupNotSnapshot { group, component } => write!(
+				f,
+				"Component {component:?} of group {group:?} does not have snapshots enabled."
+			),
+			Self::MissingGroup => write!(f, "Snapshot group is no longer in the data."),
+			Self::ComponentsChanged => write!(f, "Group components changed since the snapshot."),
+		}
+	}
+}
+impl Error for SnapshotError {}
+
+/// The last snapshots of a [Data](crate::group::Data), tagged by tick.
+///
+/// The oldest snapshots are dropped once there are more than `capacity` or
+/// their total size is over `budget` bytes. The newest is always kept.
+pub struct SnapshotBuffer<Key: slotmap::Key> {
+	snapshots: VecDeque<(u64, DataSnapshot<Key>)>,
+	capacity: usize,
+	budget: usize,
+	size: usize,
+}
+impl<Key: slotmap::Key> SnapshotBuffer<Key> {
+	pub fn new(capacity: usize, budget: usize) -> Self {
+		Self {
+			snapshots: VecDeque::with_capacity(capacity),
+			capacity,
+			budget,
+			size: 0,
+		}
+	}
+	pub fn len(&self) -> usize {
+		self.snapshots.len()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.snapshots.is_empty()
+	}
+	/// Estimated bytes held by every snapshot in the buffer.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+	pub fn push(&mut self, tick: u64, snapshot: DataSnapshot<Key>) {
+		self.size += snapshot.size;
+		self.snapshots.push_back((tick, snapshot));
+		while self.snapshots.len() > 1
+			&& (self.snapshots.len() > self.capacity || self.size > self.budget)
+		{
+			let (_, oldest) = self.snapshots.pop_front().unwrap();
+			self.size -= oldest.size;
+		}
+	}
+	pub fn latest(&self) -> Option<(u64, &DataSnapshot<Key>)> {
+		self.snapshots.back().map(|(tick, s)| (*tick, s))
+	}
+	pub fn get(&self, tick: u64) -> Option<&DataSnapshot<Key>> {
+		self.snapshots
+			.iter()
+			.find(|(t, _)| *t == tick)
+			.map(|(_, s)| s)
+	}
+	/// Removes and returns the newest snapshot, for undo.
+	pub fn pop(&mut self) -> Option<(u64, DataSnapshot<Key>)> {
+		let (tick, snapshot) = self.snapshots.pop_back()?;
+		self.size -= snapshot.size;
+		Some((tick, snapshot))
+	}
+	/// Drops every snapshot newer than `tick` and returns the one at `tick`,
+	/// for rollback.
+	pub fn rollback(&mut self, tick: u64) -> Option<&DataSnapshot<Key>> {
+		while self.snapshots.back().is_some_and(|(t, _)| *t > tick) {
+			self.pop();
+		}
+		self.snapshots
+			.back()
+			.filter(|(t, _)| *t == tick)
+			.map(|(_, s)| s)
+	}
+	pub fn clear(&mut self) {
+		self.snapshots.clear();
+		self.size = 0;
+	}
+}
@@ -0,0 +1,347 @@
+use std::{collections::HashMap, error::Error, hash::Hash, marker::PhantomData};
+
+use crate::{group::Data, method::GroupMethod, resource::MissingResource, signals::SignalError};
+
+/// The current state of type `S`, stored as a [Data] resource.
+///
+/// Methods may read it through `Res<State<S>>` and request a transition
+/// through `ResMut<State<S>>`, which the [StateMachine] applies on its next
+/// update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State<S> {
+	current: S,
+	next: Option<S>,
+}
+impl<S: Copy + Eq> State<S> {
+	pub fn new(initial: S) -> Self {
+		Self {
+			current: initial,
+			next: None,
+		}
+	}
+	pub fn get(&self) -> S {
+		self.current
+	}
+	pub fn pending(&self) -> Option<S> {
+		self.next
+	}
+	/// Requests a transition, replacing any pending one.
+	pub fn set(&mut self, next: S) {
+		self.next = Some(next);
+	}
+}
+
+/// Group resource marking the group as belonging to a state.
+///
+/// Its rows are deleted and the group is removed from [Data] when the state
+/// is exited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateScoped<S>(pub S);
+
+/// Runs method sets as a [State] resource is entered, exited and updated.
+pub struct StateMachine<S, Key: slotmap::Key> {
+	enter: HashMap<S, Vec<GroupMethod>>,
+	exit: HashMap<S, Vec<GroupMethod>>,
+	update: HashMap<S, Vec<GroupMethod>>,
+	/// The state whose enter methods last ran without failing.
+	entered: Option<S>,
+	_marker: PhantomData<Key>,
+}
+impl<S: Copy + Eq + Hash + 'static, Key: slotmap::Key> Default for StateMachine<S, Key> {
+	fn default() -> Self {
+		Self {
+			enter: HashMap::new(),
+			exit: HashMap::new(),
+			update: HashMap::new(),
+			entered: None,
+			_marker: PhantomData,
+		}
+	}
+}
+impl<S: Copy + Eq + Hash + 'static, Key: slotmap::Key> StateMachine<S, Key> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	pub fn on_enter(&mut self, state: S, method: GroupMethod) -> &mut Self {
+		self.enter.entry(state).or_default().push(method);
+		self
+	}
+	pub fn on_exit(&mut self, state: S, method: GroupMethod) -> &mut Self {
+		self.exit.entry(state).or_default().push(method);
+		self
+	}
+	pub fn on_update(&mut self, state: S, method: GroupMethod) -> &mut Self {
+		self.update.entry(state).or_default().push(method);
+		self
+	}
+	/// Enters the initial state on the first call, applies a pending
+	/// transition, then runs the update methods of the current state.
+	///
+	/// A transition stays pending until it is done. If an exit method fails
+	/// the state is not changed, and if an enter method fails the enter
+	/// methods run again on the next update.
+	///
+	/// Requires a [State<S>] resource in `data`.
+	pub fn update(&mut self, data: &mut Data<Key>) -> Result<(), Box<dyn Error>> {
+		let (current, next) = {
+			let state = data
+				.resource::<State<S>>()
+				.ok_or(MissingResource::of::<State<S>>())?;
+			(state.current, state.next)
+		};
+		if self.entered != Some(current) {
+			Self::run(&self.enter, current, data)?;
+			self.entered = Some(current);
+		}
+		let current = match next {
+			Some(next) if next != current => {
+				self.transition(current, next, data)?;
+				next
+			}
+			Some(_) => {
+				data.resource_mut::<State<S>>().unwrap().next = None;
+				current
+			}
+			None => current,
+		};
+		Self::run(&self.update, current, data)
+	}
+	fn transition(&mut self, from: S, to: S, data: &mut Data<Key>) -> Result<(), Box<dyn Error>> {
+		Self::run(&self.exit, from, data)?;
+		let despawned = despawn_scoped(data, from);
+		data.resource_mut::<State<S>>().unwrap().current = to;
+		self.entered = None;
+		Self::run(&self.enter, to, data)?;
+		self.entered = Some(to);
+		let mut state = data.resource_mut::<State<S>>().unwrap();
+		// Unless a method requested another transition meanwhile.
+		if state.next == Some(to) {
+			state.next = None;
+		}
+		Ok(despawned?)
+	}
+	fn run(
+		methods: &HashMap<S, Vec<GroupMethod>>,
+		state: S,
+		data: &Data<Key>,
+	) -> Result<(), Box<dyn Error>> {
+		let Some(methods) = methods.get(&state) else {
+			return Ok(());
+		};
+		for method in methods {
+			method.call_data(data, &mut ())?;
+		}
+		Ok(())
+	}
+}
+
+/// Deletes every row of the groups scoped to `state`, then removes the groups.
+///
+/// Every group is removed even if [DELETE](crate::group::DELETE) methods fail,
+/// their errors are returned together.
+pub fn despawn_scoped<S: PartialEq + 'static, Key: slotmap::Key>(
+	data: &mut Data<Key>,
+	state: S,
+) -> Result<(), SignalError> {
+	let keys: Vec<Key> = data
+		.iter()
+		.filter(|(_, group)| {
+			group
+				.borrow()
+				.resource::<StateScoped<S>>()
+				.is_some_and(|scope| scope.0 == state)
+		})
+		.map(|(key, _)| key)
+		.collect();
+	let mut errors = Vec::new();
+	for key in keys {
+		if let Some(mut globule) = data.globule_mut(key) {
+			let rows: Vec<usize> = (0..globule.group().len()).rev().collect();
+			if let Err(error) = globule.delete(&rows) {
+				errors.extend(error.errors);
+			}
+		}
+		data.remove(key);
+	}
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		Err(SignalError { errors })
+	}
+}
+
+/// Schedule condition holding while the [State<S>] resource is `state`.
+pub fn in_state<Key: slotmap::Key, S: Copy + Eq + 'static>(
+	state: S,
+) -> impl Fn(&Data<Key>) -> bool {
+	move |data| {
+		data.resource::<State<S>>()
+			.is_some_and(|s| s.current == state)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use slotmap::DefaultKey;
+
+	use super::*;
+	use crate::group::{DELETE, Group};
+
+	#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+	enum Screen {
+		Menu,
+		Game,
+	}
+
+	type Log = Vec<&'static str>;
+	/// The entry whose method fails.
+	struct Fail(Option<&'static str>);
+
+	fn log(entry: &'static str) -> GroupMethod {
+		GroupMethod::new(Vec::new(), move |glob, _| {
+			if glob
+				.resource::<Fail>()
+				.is_some_and(|fail| fail.0 == Some(entry))
+			{
+				return Err(entry.into());
+			}
+			glob.resource_mut::<Log>().unwrap().push(entry);
+			Ok(())
+		})
+	}
+
+	/// One group, so every method runs once per update.
+	fn data() -> Data<DefaultKey> {
+		let mut data = Data::new();
+		data.insert_resource(State::new(Screen::Menu));
+		data.insert_resource(Log::new());
+		data.insert_resource(Fail(None));
+		data.insert(Group::default().into());
+		data
+	}
+
+	fn machine() -> StateMachine<Screen, DefaultKey> {
+		let mut machine = StateMachine::new();
+		machine
+			.on_enter(Screen::Menu, log("enter menu"))
+			.on_exit(Screen::Menu, log("exit menu"))
+			.on_update(Screen::Menu, log("update menu"))
+			.on_enter(Screen::Game, log("enter game"))
+			.on_exit(Screen::Game, log("exit game"))
+			.on_update(Screen::Game, log("update game"));
+		machine
+	}
+
+	fn take_log(data: &Data<DefaultKey>) -> Log {
+		std::mem::take(&mut *data.resource_mut::<Log>().unwrap())
+	}
+
+	fn set(data: &Data<DefaultKey>, next: Screen) {
+		data.resource_mut::<State<Screen>>().unwrap().set(next);
+	}
+
+	fn fail(data: &Data<DefaultKey>, entry: Option<&'static str>) {
+		data.resource_mut::<Fail>().unwrap().0 = entry;
+	}
+
+	fn state(data: &Data<DefaultKey>) -> State<Screen> {
+		*data.resource::<State<Screen>>().unwrap()
+	}
+
+	#[test]
+	fn enter_exit_and_update_order() {
+		let mut data = data();
+		let mut machine = machine();
+		machine.update(&mut data).unwrap();
+		assert_eq!(take_log(&data), ["enter menu", "update menu"]);
+		machine.update(&mut data).unwrap();
+		assert_eq!(take_log(&data), ["update menu"]);
+
+		set(&data, Screen::Game);
+		machine.update(&mut data).unwrap();
+		assert_eq!(take_log(&data), ["exit menu", "enter game", "update game"]);
+		assert_eq!(state(&data), State::new(Screen::Game));
+
+		set(&data, Screen::Game);
+		machine.update(&mut data).unwrap();
+		assert_eq!(take_log(&data), ["update game"]);
+		assert_eq!(state(&data).pending(), None);
+	}
+
+	#[test]
+	fn failed_exit_keeps_the_transition() {
+		let mut data = data();
+		let mut machine = machine();
+		machine.update(&mut data).unwrap();
+		take_log(&data);
+
+		set(&data, Screen::Game);
+		fail(&data, Some("exit menu"));
+		assert!(machine.update(&mut data).is_err());
+		assert_eq!(take_log(&data), Log::new());
+		assert_eq!(state(&data).get(), Screen::Menu);
+		assert_eq!(state(&data).pending(), Some(Screen::Game));
+
+		fail(&data, None);
+		machine.update(&mut data).unwrap();
+		assert_eq!(take_log(&data), ["exit menu", "enter game", "update game"]);
+		assert_eq!(state(&data), State::new(Screen::Game));
+	}
+
+	#[test]
+	fn failed_enter_runs_again() {
+		let mut data = data();
+		let mut machine = machine();
+		machine.update(&mut data).unwrap();
+		take_log(&data);
+
+		set(&data, Screen::Game);
+		fail(&data, Some("enter game"));
+		assert!(machine.update(&mut data).is_err());
+		assert_eq!(take_log(&data), ["exit menu"]);
+		assert_eq!(state(&data).get(), Screen::Game);
+
+		fail(&data, None);
+		machine.update(&mut data).unwrap();
+		assert_eq!(take_log(&data), ["enter game", "update game"]);
+		assert_eq!(state(&data), State::new(Screen::Game));
+	}
+
+	#[test]
+	fn exit_despawns_scoped_groups() {
+		let mut data = Data::new();
+		data.insert_resource(State::new(Screen::Menu));
+		data.insert_resource(Log::new());
+		let keys: Vec<DefaultKey> = [Some(Screen::Menu), Some(Screen::Game), None]
+			.into_iter()
+			.map(|scope| {
+				let mut group = Group::default();
+				group.new(2).done().unwrap();
+				if let Some(scope) = scope {
+					group.insert_resource(StateScoped(scope));
+				}
+				group.connect_signal(&DELETE, |glob, rows| {
+					assert_eq!(*rows, [1, 0]);
+					glob.resource_mut::<Log>().unwrap().push("despawn");
+					Ok(())
+				});
+				data.insert(group.into())
+			})
+			.collect();
+		let mut machine = StateMachine::<Screen, DefaultKey>::new();
+		machine.update(&mut data).unwrap();
+
+		set(&data, Screen::Game);
+		machine.update(&mut data).unwrap();
+		assert_eq!(take_log(&data), ["despawn"]);
+		assert!(data.get(keys[0]).is_none());
+		assert!(data.get(keys[1]).is_some());
+		assert!(data.get(keys[2]).is_some());
+
+		set(&data, Screen::Menu);
+		machine.update(&mut data).unwrap();
+		assert_eq!(take_log(&data), ["despawn"]);
+		assert!(data.get(keys[1]).is_none());
+		assert!(data.get(keys[2]).is_some());
+	}
+}
@@ -0,0 +1,300 @@
+//! Countdowns per row, and systems delayed over a [Data].
+
+use std::{error::Error, time::Duration};
+
+use crate::{
+	component,
+	containers::{SparseSet, new_with_or_default},
+	glob::CompMut,
+	group::Data,
+	method::GroupMethod,
+	schedule::System,
+	signal,
+};
+
+/// A countdown, restarting on expiry if it has an interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timer {
+	remaining: Duration,
+	pub interval: Option<Duration>,
+	pub paused: bool,
+	done: bool,
+}
+impl Timer {
+	pub fn once(after: Duration) -> Self {
+		Self {
+			remaining: after,
+			interval: None,
+			paused: false,
+			done: false,
+		}
+	}
+	pub fn repeating(interval: Duration) -> Self {
+		assert!(!interval.is_zero(), "Repeating timer has a zero interval.");
+		Self {
+			remaining: interval,
+			interval: Some(interval),
+			paused: false,
+			done: false,
+		}
+	}
+	/// Time until the next expiry.
+	pub fn remaining(&self) -> Duration {
+		self.remaining
+	}
+	/// Whether a timer without interval has expired.
+	pub fn is_done(&self) -> bool {
+		self.done
+	}
+	/// Restarts a timer from `after`.
+	pub fn reset(&mut self, after: Duration) {
+		self.remaining = after;
+		self.done = false;
+	}
+	/// Advances by `delta`, returning how many times the timer expired,
+	/// saturating at [u32::MAX].
+	pub fn tick(&mut self, delta: Duration) -> u32 {
+		if self.paused || self.done {
+			return 0;
+		}
+		if delta < self.remaining {
+			self.remaining -= delta;
+			return 0;
+		}
+		let over = delta - self.remaining;
+		match self.interval {
+			None => {
+				self.remaining = Duration::ZERO;
+				self.done = true;
+				1
+			}
+			Some(interval) => {
+				let interval_nanos = interval.as_nanos();
+				let extra = over.as_nanos() / interval_nanos;
+				let into = (over.as_nanos() % interval_nanos) as u64;
+				self.remaining = interval - Duration::from_nanos(into);
+				u32::try_from(extra).unwrap_or(u32::MAX).saturating_add(1)
+			}
+		}
+	}
+}
+
+component!(pub Timers: SparseSet<Timer>, new_with_or_default::<Timers>, Vec<Option<Timer>>);
+
+// Rows whose timer expired, with how many times it did.
+signal!(pub TIMER_EXPIRED: &[(usize, u32)]);
+
+/// Ticks every timer by `delta`, leaving the rows that expired in `expired`
+/// with their count.
+pub fn tick_timers(
+	CompMut(mut timers): CompMut<Timers>,
+	(delta, expired): &mut (Duration, Vec<(usize, u32)>),
+) {
+	for (row, timer) in timers.iter_mut() {
+		let count = timer.tick(*delta);
+		if count > 0 {
+			expired.push((row, count));
+		}
+	}
+}
+impl Timers {
+	/// Ticks the timers of every group with [Timers], then runs
+	/// [TIMER_EXPIRED] on groups where some expired. Its methods see the
+	/// [Data]'s resources, so they can queue commands.
+	pub fn tick<Key: slotmap::Key>(
+		data: &Data<Key>,
+		delta: Duration,
+	) -> Result<(), Box<dyn Error>> {
+		for (key, _) in data.iter() {
+			let mut globule = data.globule_mut(key).unwrap();
+			if !globule.group().contains_component::<Timers>() {
+				continue;
+			}
+			let mut args = (delta, Vec::new());
+			globule.as_ref().call_method(tick_timers, &mut args)?;
+			if !args.1.is_empty() {
+				globule.call_signal(&TIMER_EXPIRED, args.1.as_slice())?;
+			}
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DelayId(u64);
+
+struct Delayed<Key: slotmap::Key> {
+	id: DelayId,
+	due: Duration,
+	interval: Option<Duration>,
+	system: Box<System<Key>>,
+}
+
+/// Systems run once time has passed, by the deltas given to
+/// [Delays::update].
+///
+/// Systems due in the same update run in the order they were due, ties in
+/// the order they were added.
+pub struct Delays<Key: slotmap::Key> {
+	elapsed: Duration,
+	next_id: u64,
+	entries: Vec<Delayed<Key>>,
+}
+impl<Key: slotmap::Key> Default for Delays<Key> {
+	fn default() -> Self {
+		Self {
+			elapsed: Duration::ZERO,
+			next_id: 0,
+			entries: Vec::new(),
+		}
+	}
+}
+impl<Key: slotmap::Key> Delays<Key> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	fn push(
+		&mut self,
+		delay: Duration,
+		interval: Option<Duration>,
+		system: Box<System<Key>>,
+	) -> DelayId {
+		let id = DelayId(self.next_id);
+		self.next_id += 1;
+		self.entries.push(Delayed {
+			id,
+			due: self.elapsed + delay,
+			interval,
+			system,
+		});
+		id
+	}
+	/// Runs `system` once, `delay` from now.
+	pub fn after(
+		&mut self,
+		delay: Duration,
+		system: impl FnMut(&Data<Key>) -> Result<(), Box<dyn Error>> + 'static,
+	) -> DelayId {
+		self.push(delay, None, Box::new(system))
+	}
+	/// Calls `method` on every group that has its dependencies, `delay` from
+	/// now.
+	pub fn after_method(&mut self, delay: Duration, method: GroupMethod) -> DelayId {
+		self.after(delay, move |data| method.call_data(data, &mut ()))
+	}
+	/// Runs `system` every `interval`, starting one interval from now.
+	pub fn every(
+		&mut self,
+		interval: Duration,
+		system: impl FnMut(&Data<Key>) -> Result<(), Box<dyn Error>> + 'static,
+	) -> DelayId {
+		assert!(!interval.is_zero(), "Repeating delay has a zero interval.");
+		self.push(interval, Some(interval), Box::new(system))
+	}
+	/// Returns whether `id` was still pending.
+	pub fn cancel(&mut self, id: DelayId) -> bool {
+		let len = self.entries.len();
+		self.entries.retain(|e| e.id != id);
+		self.entries.len() != len
+	}
+	pub fn is_pending(&self, id: DelayId) -> bool {
+		self.entries.iter().any(|e| e.id == id)
+	}
+	/// Advances by `delta` and runs what became due. A repeating system runs
+	/// once per interval passed.
+	pub fn update(&mut self, data: &Data<Key>, delta: Duration) -> Result<(), Box<dyn Error>> {
+		self.elapsed += delta;
+		while let Some(i) = (0..self.entries.len())
+			.filter(|&i| self.entries[i].due <= self.elapsed)
+			.min_by_key(|&i| (self.entries[i].due, self.entries[i].id.0))
+		{
+			let entry = &mut self.entries[i];
+			let result = (entry.system)(data);
+			match entry.interval {
+				Some(interval) => entry.due += interval,
+				None => {
+					self.entries.remove(i);
+				}
+			}
+			result?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use slotmap::DefaultKey;
+
+	use super::*;
+	use crate::{
+		commands::{CommandQueue, flush_commands},
+		group::Group,
+	};
+
+	#[test]
+	fn tick_saturates_the_count() {
+		let mut timer = Timer::repeating(Duration::from_nanos(1));
+		assert_eq!(timer.tick(Duration::from_secs(60)), u32::MAX);
+		assert_eq!(timer.remaining(), Duration::from_nanos(1));
+		let mut timer = Timer::repeating(Duration::from_millis(10));
+		assert_eq!(timer.tick(Duration::from_millis(35)), 3);
+		assert_eq!(timer.remaining(), Duration::from_millis(5));
+	}
+
+	#[test]
+	fn expired_rows_are_reported_once() {
+		let mut data = Data::<DefaultKey>::new();
+		let mut group = Group::default();
+		group.add_component::<Timers>().unwrap();
+		group
+			.new(3)
+			.with::<Timers>(vec![
+				Some(Timer::repeating(Duration::from_millis(10))),
+				None,
+				Some(Timer::once(Duration::from_millis(5))),
+			])
+			.done()
+			.unwrap();
+		group.insert_resource(Vec::<(usize, u32)>::new());
+		group.connect_signal(&TIMER_EXPIRED, |glob, expired| {
+			glob.resource_mut::<Vec<(usize, u32)>>()
+				.ok_or("Missing log.")?
+				.extend_from_slice(expired);
+			Ok(())
+		});
+		let key = data.insert(group.into());
+		Timers::tick(&data, Duration::from_millis(35)).unwrap();
+		Timers::tick(&data, Duration::from_millis(5)).unwrap();
+		let group = data[key].borrow();
+		assert_eq!(
+			*group.resource_mut::<Vec<(usize, u32)>>().unwrap(),
+			[(0, 3), (2, 1), (0, 1)]
+		);
+	}
+
+	#[test]
+	fn expiry_methods_can_queue_commands() {
+		let mut data = Data::<DefaultKey>::new();
+		data.insert_resource(CommandQueue::<DefaultKey>::new());
+		let mut group = Group::default();
+		group.add_component::<Timers>().unwrap();
+		group
+			.new(2)
+			.with::<Timers>(vec![None, Some(Timer::once(Duration::from_millis(5)))])
+			.done()
+			.unwrap();
+		group.connect_signal(&TIMER_EXPIRED, |glob, expired| {
+			let key = glob.key::<DefaultKey>().ok_or("Missing key.")?;
+			let rows: Vec<usize> = expired.iter().map(|&(row, _)| row).collect();
+			glob.resource_mut::<CommandQueue<DefaultKey>>()
+				.ok_or("Missing queue.")?
+				.delete(key, &rows);
+			Ok(())
+		});
+		let key = data.insert(group.into());
+		Timers::tick(&data, Duration::from_millis(10)).unwrap();
+		flush_commands(&mut data).unwrap();
+		assert_eq!(data[key].borrow().len(), 1);
+	}
+}
@@ -29,6 +29,22 @@ $macro_name!(
 
 pub(crate) use all_the_tuples;
 
+/// Wraps on overflow, which would otherwise fail the const evaluation of
+/// [ComponentId](crate::components::ComponentId)s for some names.
 pub(crate) const fn hash_combine(a: u64, b: u64) -> u64 {
-	a ^ (b + 0x9e3779b9 + (a << 6) + (a >> 2))
-}
\ No newline at end of file
+	a ^ (b
+		.wrapping_add(0x9e3779b9)
+		.wrapping_add(a << 6)
+		.wrapping_add(a >> 2))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::hash_combine;
+
+	#[test]
+	fn hash_combine_wraps() {
+		const HASH: u64 = hash_combine(u64::MAX, u64::MAX);
+		assert_ne!(HASH, hash_combine(u64::MAX, u64::MAX - 1));
+	}
+}
@@ -0,0 +1,123 @@
+use slotmap::DefaultKey;
+use ure_data::{
+	commands::{CommandQueue, flush_commands},
+	component,
+	components::NewArgs,
+	glob::ContMut,
+	group::{Data, Group, NEW},
+};
+
+component!(pub Ids: Vec<usize>, new_ids, Vec<usize>);
+fn new_ids(ContMut(mut ids): ContMut<Ids>, args: &mut NewArgs) {
+	ids.extend(args.take::<Ids>().unwrap());
+}
+
+fn ids(data: &Data<DefaultKey>, key: DefaultKey) -> Vec<usize> {
+	let mut ids = data[key]
+		.borrow()
+		.borrow_container::<Ids>()
+		.unwrap()
+		.clone();
+	ids.sort_unstable();
+	ids
+}
+
+fn data(groups: usize) -> (Data<DefaultKey>, Vec<DefaultKey>) {
+	let mut data = Data::new();
+	data.insert_resource(CommandQueue::<DefaultKey>::new());
+	let keys = (0..groups)
+		.map(|_| {
+			let mut group = Group::default();
+			group.add_component::<Ids>().unwrap();
+			group.new(5).with::<Ids>((0..5).collect()).done().unwrap();
+			data.insert(group.into())
+		})
+		.collect();
+	(data, keys)
+}
+
+fn queue(data: &Data<DefaultKey>) -> impl std::ops::DerefMut<Target = CommandQueue<DefaultKey>> {
+	data.resource_mut::<CommandQueue<DefaultKey>>().unwrap()
+}
+
+#[test]
+fn deletes_follow_swapped_rows() {
+	let (mut data, keys) = data(1);
+	{
+		let mut queue = queue(&data);
+		queue.delete(keys[0], &[0]);
+		// Row 4 was swapped into row 0 by the first delete.
+		queue.delete(keys[0], &[4, 1]);
+	}
+	flush_commands(&mut data).unwrap();
+	assert_eq!(ids(&data, keys[0]), [2, 3]);
+}
+
+#[test]
+fn rows_deleted_earlier_are_skipped() {
+	let (mut data, keys) = data(1);
+	{
+		let mut queue = queue(&data);
+		queue.delete(keys[0], &[1, 3]);
+		queue.delete(keys[0], &[3, 4]);
+	}
+	flush_commands(&mut data).unwrap();
+	assert_eq!(ids(&data, keys[0]), [0, 2]);
+}
+
+#[test]
+fn moves_follow_swapped_rows() {
+	let (mut data, keys) = data(2);
+	{
+		let mut queue = queue(&data);
+		queue.delete(keys[0], &[1]);
+		queue.move_rows(keys[0], keys[1], &[4, 2], |group, rows| {
+			let ids = group.borrow_container::<Ids>().unwrap();
+			let mut args = NewArgs::new(rows.len());
+			args.with::<Ids>(rows.iter().map(|&row| ids[row] + 10).collect());
+			args
+		});
+	}
+	flush_commands(&mut data).unwrap();
+	assert_eq!(ids(&data, keys[0]), [0, 3]);
+	assert_eq!(ids(&data, keys[1]), [0, 1, 2, 3, 4, 12, 14]);
+}
+
+#[test]
+fn rows_out_of_bounds_fail() {
+	let (mut data, keys) = data(1);
+	{
+		let mut queue = queue(&data);
+		let mut args = NewArgs::new(1);
+		args.with::<Ids>(vec![5]);
+		queue.new_rows(keys[0], args);
+		queue.delete(keys[0], &[5]);
+	}
+	let errors = flush_commands(&mut data).unwrap_err();
+	assert_eq!(errors.len(), 1);
+	assert_eq!(errors[0].index, 1);
+	assert_eq!(ids(&data, keys[0]), [0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn moves_delete_the_source_when_a_new_hook_fails() {
+	let (mut data, keys) = data(2);
+	data[keys[1]]
+		.borrow_mut()
+		.connect_signal(&NEW, |_, _| Err("new".into()));
+	{
+		let mut queue = queue(&data);
+		queue.move_rows(keys[0], keys[1], &[0, 1], |group, rows| {
+			let ids = group.borrow_container::<Ids>().unwrap();
+			let mut args = NewArgs::new(rows.len());
+			args.with::<Ids>(rows.iter().map(|&row| ids[row] + 10).collect());
+			args
+		});
+		queue.delete(keys[0], &[3]);
+	}
+	let errors = flush_commands(&mut data).unwrap_err();
+	assert_eq!(errors.len(), 1);
+	assert_eq!(errors[0].index, 0);
+	assert_eq!(ids(&data, keys[0]), [2, 4]);
+	assert_eq!(ids(&data, keys[1]), [0, 1, 2, 3, 4, 10, 11]);
+}
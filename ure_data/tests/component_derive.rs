@@ -0,0 +1,115 @@
+use ure_data::{
+	components::{Component, ComponentDependency, NewArgs},
+	glob::{CompRef, ContMut, ResMut},
+	group::Group,
+};
+
+#[derive(Component)]
+#[component(container = Vec<f32>)]
+pub struct Positions;
+
+#[derive(Component)]
+#[component(container = Vec<f32>, new = new_velocities)]
+pub struct Velocities;
+fn new_velocities(
+	ContMut(mut velocities): ContMut<Velocities>,
+	CompRef(positions): CompRef<Positions>,
+	args: &mut NewArgs,
+) {
+	let first = velocities.len();
+	velocities.extend(positions[first..first + args.len()].iter().map(|p| -p));
+}
+
+#[derive(Component)]
+#[component(container = Vec<u32>, depends_on(Positions))]
+pub struct Tags;
+
+#[derive(Component)]
+#[component(
+	container = Vec<f32>,
+	new = new_velocities_explicit,
+	dependencies(Velocities),
+)]
+pub struct Explicit;
+fn new_velocities_explicit(
+	ContMut(mut explicit): ContMut<Explicit>,
+	CompRef(velocities): CompRef<(Velocities, Positions)>,
+	args: &mut NewArgs,
+) {
+	let first = explicit.len();
+	explicit.extend(velocities.0[first..first + args.len()].iter().copied());
+}
+
+#[derive(Default)]
+struct Deleted(Vec<f32>);
+
+#[derive(Component)]
+#[component(
+	container = Vec<f32>,
+	new = new_healths,
+	new_arg = Vec<f32>,
+	delete = delete_healths,
+)]
+pub struct Healths;
+fn new_healths(ContMut(mut healths): ContMut<Healths>, args: &mut NewArgs) {
+	match args.take::<Healths>() {
+		Some(new) => healths.extend(new),
+		None => healths.extend(std::iter::repeat_n(1.0, args.len())),
+	}
+}
+fn delete_healths(
+	CompRef(healths): CompRef<Healths>,
+	ResMut(mut deleted): ResMut<Deleted>,
+	indices: &mut &[usize],
+) {
+	deleted.0.extend(indices.iter().map(|&i| healths[i]));
+}
+
+#[test]
+fn dependencies_are_inferred_or_listed() {
+	assert!(Positions::dependencies().is_empty());
+	assert_eq!(Velocities::dependencies(), [Velocities::ID, Positions::ID]);
+	assert_eq!(Tags::dependencies(), [Positions::ID]);
+	assert_eq!(Explicit::dependencies(), [Velocities::ID]);
+}
+
+#[test]
+fn dependencies_are_checked_when_added() {
+	let mut group = Group::default();
+	assert!(group.add_component::<Velocities>().is_err());
+	assert!(group.add_component::<Tags>().is_err());
+	group.add_component::<Positions>().unwrap();
+	group.add_component::<Velocities>().unwrap();
+	group.add_component::<Tags>().unwrap();
+	group.add_component::<Explicit>().unwrap();
+
+	group.new(2).done().unwrap();
+	assert_eq!(
+		*group.borrow_component::<Velocities>().unwrap(),
+		[-0.0, -0.0]
+	);
+	assert_eq!(group.borrow_component::<Explicit>().unwrap().len(), 2);
+	assert!(group.diverging_components().is_empty());
+}
+
+#[test]
+fn delete_runs_before_rows_are_removed() {
+	let mut group = Group::default();
+	group.insert_resource(Deleted::default());
+	group.add_component::<Healths>().unwrap();
+	group
+		.new(3)
+		.with::<Healths>(vec![3.0, 4.0, 5.0])
+		.done()
+		.unwrap();
+	group.new(1).done().unwrap();
+	group.delete(&[3, 0]).unwrap();
+	assert_eq!(group.resource::<Deleted>().unwrap().0, [1.0, 3.0]);
+	assert_eq!(*group.borrow_component::<Healths>().unwrap(), [5.0, 4.0]);
+}
+
+#[test]
+fn diagnostics() {
+	let cases = trybuild::TestCases::new();
+	cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,101 @@
+use ure_data::{
+	component,
+	components::NewArgs,
+	containers::{Container, NewDefault, NewWith, Snapshot},
+	glob::ContMut,
+	group::Group,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Container)]
+pub struct Body {
+	pub position: [f32; 2],
+	pub mass: f32,
+}
+
+#[derive(Debug, Clone, Default, Container)]
+#[container(name = Tags, crate = ure_data)]
+pub struct Tag {
+	pub id: u32,
+}
+
+component!(pub Bodies: BodySoa, new_bodies, Vec<Body>);
+fn new_bodies(ContMut(mut bodies): ContMut<Bodies>, args: &mut NewArgs) {
+	match args.take::<Bodies>() {
+		Some(new) => bodies.new_with(new),
+		None => bodies.new_default(args.len()),
+	}
+}
+
+fn body(x: f32, mass: f32) -> Body {
+	Body {
+		position: [x, 0.0],
+		mass,
+	}
+}
+
+#[test]
+fn fields_are_separate_vecs() {
+	let mut bodies = BodySoa::default();
+	bodies.push(body(1.0, 10.0));
+	bodies.push(body(2.0, 20.0));
+	assert_eq!(bodies.len(), 2);
+	assert_eq!(bodies.mass, [10.0, 20.0]);
+	assert_eq!(bodies.slices().position, [[1.0, 0.0], [2.0, 0.0]]);
+	bodies.slices_mut().mass[0] = 11.0;
+	assert_eq!(bodies.swap_remove(0), body(1.0, 11.0));
+	assert_eq!(bodies.len(), 1);
+
+	let mut tags = Tags::default();
+	tags.new_default(3);
+	assert_eq!(tags.id, [0, 0, 0]);
+	assert_eq!(tags.row_count(), Some(3));
+}
+
+#[test]
+fn rows_follow_the_group() {
+	let mut group = Group::default();
+	group.add_component::<Bodies>().unwrap();
+	group
+		.new(4)
+		.with_fn::<Bodies, _>(|i| body(i as f32, 1.0))
+		.done()
+		.unwrap();
+	group.new(1).done().unwrap();
+	{
+		let bodies = group.borrow_component::<Bodies>().unwrap();
+		assert_eq!(bodies.len(), 5);
+		assert_eq!(bodies.mass(), [1.0, 1.0, 1.0, 1.0, 0.0]);
+	}
+
+	group.delete(&[1]).unwrap();
+	{
+		let bodies = group.borrow_component::<Bodies>().unwrap();
+		let xs: Vec<f32> = bodies.position().iter().map(|p| p[0]).collect();
+		assert_eq!(xs, [0.0, 0.0, 2.0, 3.0]);
+	}
+
+	group.permute(&[3, 2, 1, 0]).unwrap();
+	{
+		let mut bodies = group.borrow_component_mut::<Bodies>().unwrap();
+		let xs: Vec<f32> = bodies.position().iter().map(|p| p[0]).collect();
+		assert_eq!(xs, [3.0, 2.0, 0.0, 0.0]);
+		bodies.mass_mut()[0] = 5.0;
+		assert_eq!(bodies.slices().mass[0], 5.0);
+	}
+	assert!(group.diverging_components().is_empty());
+
+	group.clear().unwrap();
+	assert_eq!(group.borrow_component::<Bodies>().unwrap().len(), 0);
+}
+
+#[test]
+fn snapshot_restores_every_field() {
+	let mut bodies = BodySoa::default();
+	bodies.new_with(vec![body(1.0, 1.0), body(2.0, 2.0)]);
+	let snapshot = bodies.snapshot();
+	bodies.delete(&[0]);
+	bodies.mass[0] = 9.0;
+	bodies.restore(&snapshot);
+	assert_eq!(bodies.position, [[1.0, 0.0], [2.0, 0.0]]);
+	assert_eq!(bodies.mass, [1.0, 2.0]);
+}
@@ -0,0 +1,119 @@
+use slotmap::DefaultKey;
+use ure_data::{
+	commands::{CommandQueue, flush_commands},
+	component,
+	components::{NewArgs, NewArgsError},
+	glob::{ContMut, ResMut},
+	group::{DELETE, Data, Group, PERMUTE},
+	signal,
+};
+
+signal!(pub PING: usize);
+
+#[derive(Default)]
+struct Log(Vec<String>);
+
+component!(pub Rows: Vec<usize>, new_rows; delete_rows);
+fn new_rows(ContMut(mut rows): ContMut<Rows>, ResMut(mut log): ResMut<Log>, args: &mut NewArgs) {
+	let first = rows.len();
+	rows.extend(first..first + args.len());
+	log.0.push(format!("new {}", args.len()));
+}
+fn delete_rows(ResMut(mut log): ResMut<Log>, indices: &mut &[usize]) {
+	log.0.push(format!("delete {indices:?}"));
+}
+
+fn data() -> (Data<DefaultKey>, DefaultKey) {
+	let mut data = Data::new();
+	data.insert_resource(Log::default());
+	data.insert_resource(CommandQueue::<DefaultKey>::new());
+	let mut group = Group::default();
+	group.add_component::<Rows>().unwrap();
+	group.connect_signal(&PERMUTE, |glob, order| {
+		glob.resource_mut::<Log>()
+			.ok_or("Missing Log.")?
+			.0
+			.push(format!("permute {order:?}"));
+		Ok(())
+	});
+	let key = data.insert(group.into());
+	(data, key)
+}
+
+fn log(data: &Data<DefaultKey>) -> Vec<String> {
+	std::mem::take(&mut data.resource_mut::<Log>().unwrap().0)
+}
+
+#[test]
+fn hooks_see_data_resources_through_globules() {
+	let (data, key) = data();
+	let mut globule = data.globule_mut(key).unwrap();
+	globule.new(3).done().unwrap();
+	globule.new_from_args(NewArgs::new(1)).unwrap();
+	globule.delete(&[0]).unwrap();
+	globule.permute(&[2, 1, 0]).unwrap();
+	globule.clear().unwrap();
+	drop(globule);
+	assert_eq!(
+		log(&data),
+		[
+			"new 3",
+			"new 1",
+			"delete [0]",
+			"permute [2, 1, 0]",
+			"delete [0, 1, 2]"
+		]
+	);
+}
+
+#[test]
+fn hooks_see_data_resources_from_commands() {
+	let (mut data, key) = data();
+	data.globule_mut(key).unwrap().new(1).done().unwrap();
+	{
+		let mut queue = data.resource_mut::<CommandQueue<DefaultKey>>().unwrap();
+		queue.new_rows(key, NewArgs::new(2));
+		queue.delete(key, &[0]);
+	}
+	flush_commands(&mut data).unwrap();
+	assert_eq!(log(&data), ["new 1", "new 2", "delete [0]"]);
+	assert_eq!(data[key].borrow().len(), 2);
+}
+
+#[test]
+fn hook_errors_are_returned_after_the_change() {
+	let (data, key) = data();
+	let mut group = data[key].borrow_mut();
+	// Through the group directly, the hooks cannot see the Data's Log.
+	match group.new(2).done() {
+		Err(NewArgsError::Signal(error)) => assert_eq!(error.errors.len(), 1),
+		result => panic!("expected a signal error, got {result:?}"),
+	}
+	assert_eq!(group.len(), 2);
+	drop(group);
+	assert!(log(&data).is_empty());
+
+	let mut group = Group::default();
+	group.new(2).done().unwrap();
+	group.connect_signal(&PERMUTE, |_, _| Err("permute".into()));
+	group.connect_signal(&DELETE, |_, _| Err("delete".into()));
+	assert_eq!(
+		group.permute(&[1, 0]).unwrap_err().to_string(),
+		"Signal methods failed: permute"
+	);
+	assert_eq!(group.delete(&[0]).unwrap_err().errors.len(), 1);
+	assert_eq!(group.len(), 1);
+}
+
+#[test]
+fn every_handler_runs_after_one_fails() {
+	let (data, key) = data();
+	let mut group = data[key].borrow_mut();
+	group.connect_signal(&PING, |_, _| Err("first".into()));
+	group.connect_signal(&PING, |_, count| {
+		*count += 1;
+		Err("second".into())
+	});
+	let error = group.call_signal(&PING, 0).unwrap_err();
+	assert_eq!(error.to_string(), "Signal methods failed: first second");
+}
@@ -0,0 +1,60 @@
+use indexmap::IndexSet;
+use slotmap::DefaultKey;
+use ure_data::{
+	component,
+	components::NewArgs,
+	glob::{ContMut, Glob},
+	group::{Data, Group},
+	signal,
+};
+
+component!(pub Names: IndexSet<&'static str>, new_names, Vec<&'static str>);
+fn new_names(ContMut(mut names): ContMut<Names>, args: &mut NewArgs) {
+	names.extend(args.take::<Names>().unwrap());
+}
+
+signal!(pub PING: usize);
+
+fn keys(glob: &Glob<DefaultKey, &'static str, Names>, data: &Data<DefaultKey>) -> Vec<DefaultKey> {
+	glob.iter(data)
+		.map(|globule| globule.as_ref().key().unwrap())
+		.collect()
+}
+
+#[test]
+fn glob_iterates_groups_in_the_order_they_were_added() {
+	let mut data = Data::new();
+	let [a, b, c, d] = [(); 4].map(|_| data.insert(Group::default().into()));
+	let mut glob = Glob::<DefaultKey, &'static str, Names>::new();
+	for key in [c, a, b] {
+		glob.add_group(key);
+	}
+	assert_eq!(keys(&glob, &data), [c, a, b]);
+	glob.remove_group(c);
+	glob.add_group(d);
+	glob.add_group(c);
+	assert_eq!(keys(&glob, &data), [a, b, d, c]);
+	// Groups removed from the data are skipped, the rest keep their order.
+	data.remove(b);
+	assert_eq!(keys(&glob, &data), [a, d, c]);
+}
+
+#[test]
+fn signal_handlers_run_in_the_order_they_were_connected() {
+	let mut group = Group::default();
+	group.insert_resource(Vec::<usize>::new());
+	for handler in [2, 0, 1] {
+		group.connect_signal(&PING, move |glob, ping| {
+			glob.resource_mut::<Vec<usize>>()
+				.ok_or("Missing log.")?
+				.push(*ping * 10 + handler);
+			Ok(())
+		});
+	}
+	group.call_signal(&PING, 1).unwrap();
+	group.call_signal(&PING, 2).unwrap();
+	assert_eq!(
+		*group.resource_mut::<Vec<usize>>().unwrap(),
+		[12, 10, 11, 22, 20, 21]
+	);
+}
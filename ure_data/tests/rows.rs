@@ -0,0 +1,115 @@
+use ure_data::{
+	cell::{Ref, RefMut},
+	component,
+	components::NewArgs,
+	containers::{Container, SparseMap, SparseSet, new_with_or_default, permute_by_swaps},
+	glob::{CompRef, ContMut},
+	group::Group,
+};
+
+/// Overrides nothing but what a container must implement.
+#[derive(Default)]
+pub struct Plain(Vec<usize>);
+impl Container for Plain {
+	type Ref<'a> = Ref<'a, Vec<usize>>;
+	type RefMut<'a> = RefMut<'a, Vec<usize>>;
+
+	fn as_ref<'a>(cont: Ref<'a, Self>) -> Self::Ref<'a> {
+		Ref::map(cont, |c| &c.0)
+	}
+	fn as_mut<'a>(cont: RefMut<'a, Self>) -> Self::RefMut<'a> {
+		RefMut::map(cont, |c| &mut c.0)
+	}
+	fn delete(&mut self, indices: &[usize]) {
+		for &i in indices {
+			self.0.swap_remove(i);
+		}
+	}
+	fn permute(&mut self, order: &[usize]) {
+		permute_by_swaps(order, |a, b| self.0.swap(a, b));
+	}
+}
+
+component!(pub Plains: Plain, new_plains, Vec<usize>);
+fn new_plains(ContMut(mut plains): ContMut<Plains>, args: &mut NewArgs) {
+	plains.0.extend(args.take::<Plains>().unwrap());
+}
+
+component!(pub Ids: Vec<usize>, new_ids, Vec<usize>);
+fn new_ids(ContMut(mut ids): ContMut<Ids>, args: &mut NewArgs) {
+	ids.extend(args.take::<Ids>().unwrap());
+}
+component!(pub EvenSet: SparseSet<usize>, new_with_or_default::<EvenSet>, Vec<Option<usize>>);
+component!(pub EvenMap: SparseMap<usize>, new_with_or_default::<EvenMap>, Vec<Option<usize>>);
+
+/// Rows holding ids `0..len` in every container, the sparse ones only on
+/// even ids.
+fn group(len: usize) -> Group {
+	let mut group = Group::default();
+	group.add_component::<Ids>().unwrap();
+	group.add_component::<Plains>().unwrap();
+	group.add_component::<EvenSet>().unwrap();
+	group.add_component::<EvenMap>().unwrap();
+	let even = || (0..len).map(|i| (i % 2 == 0).then_some(i)).collect();
+	group
+		.new(len)
+		.with::<Ids>((0..len).collect())
+		.with::<Plains>((0..len).collect())
+		.with::<EvenSet>(even())
+		.with::<EvenMap>(even())
+		.done()
+		.unwrap();
+	group
+}
+
+/// The id of every row, checking each container agrees.
+fn aligned_ids(group: &Group) -> Vec<usize> {
+	let ids = group.borrow_container::<Ids>().unwrap().clone();
+	let plains = group.borrow_container::<Plains>().unwrap();
+	let set = group.borrow_container::<EvenSet>().unwrap();
+	let map = group.borrow_container::<EvenMap>().unwrap();
+	assert_eq!(plains.0, ids);
+	assert_eq!(set.rows(), ids.len());
+	for (row, &id) in ids.iter().enumerate() {
+		let even = (id % 2 == 0).then_some(&id);
+		assert_eq!(set.get(row), even, "set row {row}");
+		assert_eq!(map.get(row), even, "map row {row}");
+	}
+	ids
+}
+
+#[test]
+fn permute_keeps_containers_aligned() {
+	let mut group = group(6);
+	group.permute(&[3, 0, 5, 1, 4, 2]).unwrap();
+	assert_eq!(aligned_ids(&group), [3, 0, 5, 1, 4, 2]);
+
+	group.delete(&[1]).unwrap();
+	assert_eq!(aligned_ids(&group), [3, 2, 5, 1, 4]);
+}
+
+#[test]
+fn sort_by_keeps_containers_aligned() {
+	fn descending(CompRef(ids): CompRef<Ids>, order: &mut [usize]) {
+		order.sort_by_key(|&row| std::cmp::Reverse(ids[row]));
+	}
+	let mut group = group(5);
+	group.permute(&[2, 4, 0, 3, 1]).unwrap();
+	group
+		.sort_by(|glob, order| glob.call_method(descending, order))
+		.unwrap();
+	assert_eq!(aligned_ids(&group), [4, 3, 2, 1, 0]);
+}
+
+#[test]
+fn clear_deletes_every_row_by_default() {
+	let mut group = Group::default();
+	group.add_component::<Plains>().unwrap();
+	group.new(3).with::<Plains>(vec![0, 1, 2]).done().unwrap();
+	group.clear().unwrap();
+	assert!(group.is_empty());
+	assert!(group.borrow_container::<Plains>().unwrap().0.is_empty());
+
+	group.new(1).with::<Plains>(vec![3]).done().unwrap();
+	assert_eq!(group.borrow_container::<Plains>().unwrap().0, [3]);
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "scripting")]
+
+use slotmap::DefaultKey;
+use ure_data::{
+	component,
+	components::NewArgs,
+	glob::ContMut,
+	group::{Data, Group},
+	scripting::Scripts,
+};
+
+component!(pub Hps: Vec<f32>, new_hps);
+fn new_hps(ContMut(mut hps): ContMut<Hps>, args: &mut NewArgs) {
+	hps.extend(std::iter::repeat_n(0.0, args.len()));
+}
+
+#[test]
+fn connect_new_sees_the_rows_being_created() {
+	let scripts = Scripts::<DefaultKey>::new();
+	scripts
+		.load(
+			r#"
+			fn on_new(first, count) {
+				if len() != first {
+					throw "first is not the old length";
+				}
+				for i in first..first + count {
+					set("Hps", i, "", get("Hps", i) + 5.0);
+				}
+			}
+			"#,
+		)
+		.unwrap();
+	let mut group = Group::default();
+	group.add_component::<Hps>().unwrap();
+	group.enable_reflect::<Hps>().unwrap();
+	scripts.connect_new(&mut group, "on_new");
+	let mut data = Data::<DefaultKey>::new();
+	let key = data.insert(group.into());
+	let mut globule = data.globule_mut(key).unwrap();
+	globule.new(2).done().unwrap();
+	globule.new(3).done().unwrap();
+	drop(globule);
+	assert_eq!(
+		*data[key].borrow().borrow_container::<Hps>().unwrap(),
+		[5.0; 5]
+	);
+}
+
+#[test]
+fn rows_past_those_being_created_are_missing() {
+	let scripts = Scripts::<DefaultKey>::new();
+	scripts
+		.load(r#"fn on_new(first, count) { set("Hps", first + count, "", 1.0); }"#)
+		.unwrap();
+	let mut group = Group::default();
+	group.add_component::<Hps>().unwrap();
+	group.enable_reflect::<Hps>().unwrap();
+	scripts.connect_new(&mut group, "on_new");
+	assert!(group.new(2).done().is_err());
+	assert_eq!(group.len(), 2);
+}
@@ -0,0 +1,55 @@
+use slotmap::{DefaultKey, Key};
+use ure_data::{
+	component,
+	components::{Component, NewArgs},
+	glob::ContMut,
+	group::{Data, Group},
+	snapshot::SnapshotError,
+};
+
+component!(pub Hps: Vec<f32>, new_hps);
+fn new_hps(ContMut(mut hps): ContMut<Hps>, args: &mut NewArgs) {
+	hps.extend(std::iter::repeat_n(1.0, args.len()));
+}
+
+fn group(snapshot: bool) -> Group {
+	let mut group = Group::default();
+	group.add_component::<Hps>().unwrap();
+	if snapshot {
+		group.enable_snapshot::<Hps>().unwrap();
+	}
+	group.new(2).done().unwrap();
+	group
+}
+
+#[test]
+fn restores_every_group() {
+	let mut data = Data::<DefaultKey>::new();
+	let a = data.insert(group(true).into());
+	let b = data.insert(group(true).into());
+	let snapshot = data.snapshot().unwrap();
+	assert_eq!(snapshot.keys().collect::<Vec<_>>(), [a, b]);
+	data[a].borrow_mut().delete(&[0]).unwrap();
+	data[b].borrow().borrow_container_mut::<Hps>().unwrap()[1] = 5.0;
+	data.restore(&snapshot).unwrap();
+	assert_eq!(data[a].borrow().len(), 2);
+	assert_eq!(
+		*data[b].borrow().borrow_container::<Hps>().unwrap(),
+		[1.0, 1.0]
+	);
+}
+
+#[test]
+fn names_the_group_without_snapshots() {
+	let mut data = Data::<DefaultKey>::new();
+	data.insert(group(true).into());
+	let key = data.insert(group(false).into());
+	match data.snapshot() {
+		Err(SnapshotError::GroupNotSnapshot { group, component }) => {
+			assert_eq!(group, key.data());
+			assert_eq!(component, Hps::ID);
+		}
+		Err(error) => panic!("{error}"),
+		Ok(_) => panic!("captured a group without snapshots"),
+	}
+}
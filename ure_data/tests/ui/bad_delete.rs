@@ -0,0 +1,10 @@
+use ure_data::components::Component;
+
+#[derive(Component)]
+#[component(container = Vec<f32>, delete = delete_positions)]
+pub struct Positions;
+fn delete_positions(_indices: Vec<usize>) -> bool {
+	true
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use ure_data::{components::Component, glob::ContMut};
+
+#[derive(Component)]
+#[component(container = Vec<f32>, new = new_positions)]
+pub struct Positions;
+fn new_positions(ContMut(_positions): ContMut<Positions>, _count: usize) {}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use ure_data::components::Component;
+
+#[derive(Component)]
+#[component(container = Vec<f32>)]
+pub struct Positions;
+
+#[derive(Component)]
+#[component(container = Vec<f32>, depends_on(Positions), dependencies(Positions))]
+pub struct Velocities;
+
+fn main() {}
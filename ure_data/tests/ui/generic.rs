@@ -0,0 +1,7 @@
+use ure_data::components::Component;
+
+#[derive(Component)]
+#[component(container = Vec<T>)]
+pub struct Positions<T>(T);
+
+fn main() {}
@@ -0,0 +1,7 @@
+use ure_data::components::Component;
+
+#[derive(Component)]
+#[component(new = ure_data::components::new_default::<Positions>)]
+pub struct Positions;
+
+fn main() {}
@@ -0,0 +1,7 @@
+use ure_data::components::Component;
+
+#[derive(Component)]
+#[component(container = Vec<f32>, deleet = delete_positions)]
+pub struct Positions;
+
+fn main() {}
@@ -0,0 +1,169 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{DeriveInput, Expr, Path, Type, spanned::Spanned};
+
+#[derive(Default)]
+struct Attributes {
+	container: Option<Type>,
+	new: Option<Expr>,
+	new_arg: Option<Type>,
+	delete: Option<Expr>,
+	depends_on: Vec<Path>,
+	dependencies: Option<(Path, Vec<Path>)>,
+	krate: Option<Path>,
+}
+
+fn parse_attributes(input: &DeriveInput) -> syn::Result<Attributes> {
+	let mut attributes = Attributes::default();
+	for attr in input.attrs.iter() {
+		if !attr.path().is_ident("component") {
+			continue;
+		}
+		attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("container") {
+				attributes.container = Some(meta.value()?.parse()?);
+			} else if meta.path.is_ident("new") {
+				attributes.new = Some(meta.value()?.parse()?);
+			} else if meta.path.is_ident("new_arg") {
+				attributes.new_arg = Some(meta.value()?.parse()?);
+			} else if meta.path.is_ident("delete") {
+				attributes.delete = Some(meta.value()?.parse()?);
+			} else if meta.path.is_ident("crate") {
+				attributes.krate = Some(meta.value()?.parse()?);
+			} else if meta.path.is_ident("depends_on") {
+				meta.parse_nested_meta(|dependency| {
+					attributes.depends_on.push(dependency.path);
+					Ok(())
+				})?;
+			} else if meta.path.is_ident("dependencies") {
+				let mut dependencies = Vec::new();
+				meta.parse_nested_meta(|dependency| {
+					dependencies.push(dependency.path);
+					Ok(())
+				})?;
+				attributes.dependencies = Some((meta.path.clone(), dependencies));
+			} else {
+				return Err(meta.error(
+					"expected `container`, `new`, `new_arg`, `delete`, `depends_on`, `dependencies` or `crate`",
+				));
+			}
+			Ok(())
+		})?;
+	}
+	Ok(attributes)
+}
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+	if !input.generics.params.is_empty() {
+		return Err(syn::Error::new_spanned(
+			&input.generics,
+			"`Component` cannot be derived for generic types",
+		));
+	}
+	let attributes = parse_attributes(&input)?;
+	let Some(container) = attributes.container else {
+		return Err(syn::Error::new_spanned(
+			&input.ident,
+			"missing `#[component(container = ...)]`",
+		));
+	};
+
+	let name = &input.ident;
+	let krate = crate::crate_path(attributes.krate);
+	let new_arg = match attributes.new_arg {
+		Some(new_arg) => quote!(#new_arg),
+		None => quote!(()),
+	};
+	if let Some((path, _)) = &attributes.dependencies
+		&& !attributes.depends_on.is_empty()
+	{
+		return Err(syn::Error::new_spanned(
+			path,
+			"`dependencies` lists every dependency, it cannot be used with `depends_on`",
+		));
+	}
+
+	let (new_dependencies, new) = match &attributes.new {
+		Some(new) => (
+			quote_spanned! {new.span()=>
+				dependencies.extend(#krate::components::NewMethod::dependencies(&(#new)));
+			},
+			quote_spanned! {new.span()=>
+				#krate::components::NewMethod::call_new(#new, glob, args)
+			},
+		),
+		None => (
+			quote!(),
+			quote! {
+				#krate::components::NewMethod::call_new(
+					#krate::components::new_default::<Self>,
+					glob,
+					args,
+				)
+			},
+		),
+	};
+	let (delete_dependencies, delete) = match &attributes.delete {
+		Some(delete) => (
+			quote_spanned! {delete.span()=>
+				dependencies.extend(#krate::components::DeleteMethod::dependencies(&(#delete)));
+			},
+			{
+				let call = quote_spanned! {delete.span()=>
+					#krate::components::DeleteMethod::call_delete(#delete, glob, indices)
+				};
+				quote! {
+					fn delete(
+						glob: #krate::glob::GlobuleRef<'_, '_>,
+						indices: &mut &[usize],
+					) -> ::std::result::Result<(), ::std::boxed::Box<dyn ::std::error::Error>> {
+						#call
+					}
+				}
+			},
+		),
+		None => (quote!(), quote!()),
+	};
+
+	let dependencies = match &attributes.dependencies {
+		Some((_, dependencies)) => quote! {
+			::std::vec![#(<#dependencies as #krate::components::Component>::ID),*]
+		},
+		None => {
+			let depends_on = attributes.depends_on.iter();
+			quote! {
+				let mut dependencies = ::std::vec![
+					#(<#depends_on as #krate::components::Component>::ID),*
+				];
+				#new_dependencies
+				#delete_dependencies
+				dependencies
+			}
+		}
+	};
+
+	Ok(quote! {
+		impl #krate::components::ComponentDependency for #name {
+			#[allow(unused_mut)]
+			fn dependencies() -> ::std::vec::Vec<#krate::components::ComponentId> {
+				#dependencies
+			}
+		}
+		impl #krate::components::Component for #name {
+			const ID: #krate::components::ComponentId = #krate::components::ComponentId::new(
+				::std::module_path!(),
+				::std::stringify!(#name),
+			);
+			type Container = #container;
+
+			type NewArg = #new_arg;
+			fn new(
+				glob: #krate::glob::GlobuleRef<'_, '_>,
+				args: &mut #krate::components::NewArgs,
+			) -> ::std::result::Result<(), ::std::boxed::Box<dyn ::std::error::Error>> {
+				#new
+			}
+			#delete
+		}
+	})
+}
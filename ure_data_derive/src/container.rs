@@ -0,0 +1,240 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, Ident, Path};
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+	if !input.generics.params.is_empty() {
+		return Err(syn::Error::new_spanned(
+			&input.generics,
+			"`Container` cannot be derived for generic structs",
+		));
+	}
+	let Data::Struct(data) = &input.data else {
+		return Err(syn::Error::new_spanned(
+			&input.ident,
+			"`Container` can only be derived for structs",
+		));
+	};
+	let Fields::Named(fields) = &data.fields else {
+		return Err(syn::Error::new_spanned(
+			&data.fields,
+			"`Container` requires named fields",
+		));
+	};
+
+	let mut name = format_ident!("{}Soa", input.ident);
+	let mut krate = None;
+	for attr in input.attrs.iter() {
+		if !attr.path().is_ident("container") {
+			continue;
+		}
+		attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("name") {
+				name = meta.value()?.parse::<Ident>()?;
+				Ok(())
+			} else if meta.path.is_ident("crate") {
+				krate = Some(meta.value()?.parse::<Path>()?);
+				Ok(())
+			} else {
+				Err(meta.error("expected `name = ...` or `crate = ...`"))
+			}
+		})?;
+	}
+	let krate = crate::crate_path(krate);
+
+	let vis = &input.vis;
+	let item = &input.ident;
+	let refs = format_ident!("{}Ref", name);
+	let refs_mut = format_ident!("{}RefMut", name);
+	let slices = format_ident!("{}Slices", name);
+	let slices_mut = format_ident!("{}SlicesMut", name);
+
+	let field_vis: Vec<_> = fields.named.iter().map(|f| &f.vis).collect();
+	let field_names: Vec<_> = fields
+		.named
+		.iter()
+		.map(|f| f.ident.as_ref().unwrap())
+		.collect();
+	let field_names_mut: Vec<_> = field_names
+		.iter()
+		.map(|f| format_ident!("{}_mut", f))
+		.collect();
+	let types: Vec<_> = fields.named.iter().map(|f| &f.ty).collect();
+	let first = field_names.first();
+	let len = match first {
+		Some(first) => quote!(self.#first.len()),
+		None => quote!(0),
+	};
+
+	let doc = format!("Struct-of-arrays container for [{item}].");
+	let ref_doc = format!("Per-field slices of a [{name}].");
+
+	Ok(quote! {
+		#[doc = #doc]
+		#vis struct #name {
+			#(#field_vis #field_names: ::std::vec::Vec<#types>,)*
+		}
+		impl ::std::default::Default for #name {
+			fn default() -> Self {
+				Self {
+					#(#field_names: ::std::vec::Vec::new(),)*
+				}
+			}
+		}
+		impl #name {
+			pub fn len(&self) -> usize {
+				#len
+			}
+			pub fn is_empty(&self) -> bool {
+				self.len() == 0
+			}
+			pub fn push(&mut self, value: #item) {
+				#(self.#field_names.push(value.#field_names);)*
+			}
+			pub fn swap_remove(&mut self, index: usize) -> #item {
+				#item {
+					#(#field_names: self.#field_names.swap_remove(index),)*
+				}
+			}
+			pub fn slices(&self) -> #slices<'_> {
+				#slices {
+					#(#field_names: self.#field_names.as_slice(),)*
+				}
+			}
+			pub fn slices_mut(&mut self) -> #slices_mut<'_> {
+				#slices_mut {
+					#(#field_names: self.#field_names.as_mut_slice(),)*
+				}
+			}
+		}
+
+		#[doc = #ref_doc]
+		#vis struct #slices<'a> {
+			#(#field_vis #field_names: &'a [#types],)*
+		}
+		#[doc = #ref_doc]
+		#vis struct #slices_mut<'a> {
+			#(#field_vis #field_names: &'a mut [#types],)*
+		}
+
+		#vis struct #refs<'a>(#krate::cell::Ref<'a, #name>);
+		impl #refs<'_> {
+			pub fn len(&self) -> usize {
+				self.0.len()
+			}
+			pub fn is_empty(&self) -> bool {
+				self.0.is_empty()
+			}
+			pub fn slices(&self) -> #slices<'_> {
+				self.0.slices()
+			}
+			#(
+			pub fn #field_names(&self) -> &[#types] {
+				&self.0.#field_names
+			}
+			)*
+		}
+
+		#vis struct #refs_mut<'a>(#krate::cell::RefMut<'a, #name>);
+		impl #refs_mut<'_> {
+			pub fn len(&self) -> usize {
+				self.0.len()
+			}
+			pub fn is_empty(&self) -> bool {
+				self.0.is_empty()
+			}
+			pub fn slices(&self) -> #slices<'_> {
+				self.0.slices()
+			}
+			/// Borrows every field at once, mutably.
+			pub fn slices_mut(&mut self) -> #slices_mut<'_> {
+				self.0.slices_mut()
+			}
+			#(
+			pub fn #field_names(&self) -> &[#types] {
+				&self.0.#field_names
+			}
+			pub fn #field_names_mut(&mut self) -> &mut [#types] {
+				&mut self.0.#field_names
+			}
+			)*
+		}
+
+		impl #krate::containers::Container for #name {
+			type Ref<'a> = #refs<'a>;
+			type RefMut<'a> = #refs_mut<'a>;
+
+			fn as_ref<'a>(cont: #krate::cell::Ref<'a, Self>) -> Self::Ref<'a> {
+				#refs(cont)
+			}
+			fn as_mut<'a>(cont: #krate::cell::RefMut<'a, Self>) -> Self::RefMut<'a> {
+				#refs_mut(cont)
+			}
+			fn delete(&mut self, indices: &[usize]) {
+				for &index in indices {
+					#(self.#field_names.swap_remove(index);)*
+				}
+			}
+			fn clear(&mut self, _: usize) {
+				#(self.#field_names.clear();)*
+			}
+			fn permute(&mut self, order: &[usize]) {
+				#krate::containers::permute_by_swaps(order, |a, b| {
+					#(self.#field_names.swap(a, b);)*
+				});
+			}
+			fn row_count(&self) -> ::std::option::Option<usize> {
+				::std::option::Option::Some(self.len())
+			}
+			fn memory_usage(&self) -> usize {
+				::std::mem::size_of::<Self>()
+					#(+ self.#field_names.capacity() * ::std::mem::size_of::<#types>())*
+			}
+			fn capacity(&self) -> ::std::option::Option<usize> {
+				[#(self.#field_names.capacity()),*].into_iter().min()
+			}
+			fn reserve(&mut self, additional: usize) {
+				#(self.#field_names.reserve(additional);)*
+			}
+			fn shrink_to_fit(&mut self) {
+				#(self.#field_names.shrink_to_fit();)*
+			}
+		}
+		impl #krate::containers::NewDefault for #name
+		where
+			#(for<'__a> #types: ::std::default::Default,)*
+		{
+			fn new_default(&mut self, num: usize) {
+				#(
+				self.#field_names.extend((0..num).map(|_| <#types as ::std::default::Default>::default()));
+				)*
+			}
+		}
+		impl #krate::containers::Snapshot for #name
+		where
+			#(for<'__a> #types: ::std::clone::Clone,)*
+		{
+			fn snapshot(&self) -> Self {
+				Self {
+					#(#field_names: ::std::clone::Clone::clone(&self.#field_names),)*
+				}
+			}
+			fn restore(&mut self, snapshot: &Self) {
+				#(::std::clone::Clone::clone_from(&mut self.#field_names, &snapshot.#field_names);)*
+			}
+			fn snapshot_size(&self) -> usize {
+				0 #(+ self.#field_names.len() * ::std::mem::size_of::<#types>())*
+			}
+		}
+		impl #krate::containers::NewWith for #name {
+			type Args = ::std::vec::Vec<#item>;
+
+			fn new_with(&mut self, args: Self::Args) {
+				#(self.#field_names.reserve(args.len());)*
+				for value in args {
+					self.push(value);
+				}
+			}
+		}
+	})
+}
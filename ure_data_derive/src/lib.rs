@@ -0,0 +1,57 @@
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+mod component;
+mod container;
+
+/// Derives a struct-of-arrays container for a struct with named fields.
+///
+/// `Body { position: Vec2, velocity: Vec2 }` generates `BodySoa` holding a
+/// `Vec` per field, which implements `Container`, `NewDefault`, `NewWith` and
+/// `Snapshot`.
+/// Use `#[container(name = Bodies)]` to pick a different name, and
+/// `#[container(crate = path::to::ure_data)]` if `ure_data` is not a direct
+/// dependency.
+#[proc_macro_derive(Container, attributes(container))]
+pub fn derive_container(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	container::derive(input)
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
+
+/// Implements `Component` for a marker type.
+///
+/// ```ignore
+/// #[derive(Component)]
+/// #[component(
+///     container = Vec<Arc<Window>>,
+///     new = new_windows,
+///     new_arg = Vec<WindowAttributes>,
+///     delete = delete_windows,
+///     depends_on(Proxy),
+/// )]
+/// pub struct Windows;
+/// ```
+///
+/// Only `container` is required. Without `new` the container's `NewDefault`
+/// is used. `delete` runs before the rows are removed from every container.
+/// Dependencies of `new` and `delete` are inferred from their extractors,
+/// `depends_on` adds to them. `dependencies(A, B)` lists them explicitly
+/// instead, nothing is inferred then. `crate = path::to::ure_data` works as for
+/// [Container](derive@Container).
+#[proc_macro_derive(Component, attributes(component))]
+pub fn derive_component(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	component::derive(input)
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
+
+/// Path of `ure_data` in generated code, `::ure_data` unless given.
+fn crate_path(path: Option<syn::Path>) -> proc_macro2::TokenStream {
+	match path {
+		Some(path) => quote::quote!(#path),
+		None => quote::quote!(::ure_data),
+	}
+}